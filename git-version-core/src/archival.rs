@@ -0,0 +1,84 @@
+//! Version information of source archives made by `git archive`.
+//!
+//! Archives made by `git archive`, including the "Download ZIP" archives of GitHub, have no git repository.
+//! A project can still record its version in them by committing a `.git_archival.txt` file
+//! with the `export-subst` attribute set in `.gitattributes`, in the format used by setuptools-scm:
+//!
+//! ```text
+//! node: $Format:%H$
+//! describe-name: $Format:%(describe:tags=true)$
+//! ```
+//!
+//! `git archive` replaces the placeholders when making the archive.
+
+use std::path::Path;
+
+/// The name of the file, in the root of the archive.
+pub const FILE_NAME: &str = ".git_archival.txt";
+
+/// The contents of a `.git_archival.txt` file with its placeholders replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archival {
+	/// The full hash of the commit the archive was made from.
+	pub node: Option<String>,
+
+	/// The output of `git describe` for the commit the archive was made from.
+	pub describe_name: Option<String>,
+}
+
+impl Archival {
+	/// Find and read a `.git_archival.txt` file in the given directory or one of its parents.
+	///
+	/// Returns `None` if there is no such file, or if its placeholders were not replaced,
+	/// which happens when the sources did not come from `git archive`.
+	pub fn find(dir: &Path) -> Option<Self> {
+		dir.ancestors()
+			.find_map(|dir| std::fs::read_to_string(dir.join(FILE_NAME)).ok())
+			.and_then(|data| Self::parse(&data))
+	}
+
+	/// Parse the contents of a `.git_archival.txt` file.
+	///
+	/// Values that still contain a `$Format:` placeholder are ignored.
+	pub fn parse(data: &str) -> Option<Self> {
+		let value = |key: &str| {
+			data.lines()
+				.filter_map(|line| line.split_once(':'))
+				.find(|(name, _)| name.trim() == key)
+				.map(|(_, value)| value.trim())
+				.filter(|value| !value.is_empty() && !value.contains("$Format:") && !value.contains("%(describe"))
+				.map(|value| value.to_owned())
+		};
+		let node = value("node").filter(|x| x.bytes().all(|x| x.is_ascii_hexdigit()));
+		let describe_name = value("describe-name");
+		if node.is_none() && describe_name.is_none() {
+			return None;
+		}
+		Some(Self { node, describe_name })
+	}
+
+	/// Get the version of the archive: the `describe-name`, or else the commit hash abbreviated to 7 characters.
+	pub fn version(&self) -> Option<String> {
+		self.describe_name
+			.clone()
+			.or_else(|| self.node.as_ref().map(|node| node[..node.len().min(7)].to_owned()))
+	}
+}
+
+#[test]
+fn test_parse() {
+	use assert2::{assert, let_assert};
+
+	let data = "node: 0123456789abcdef0123456789abcdef01234567\nnode-date: 2024-01-01T00:00:00+00:00\ndescribe-name: v1.0-3-g0123456\n";
+	let_assert!(Some(archival) = Archival::parse(data));
+	assert!(archival.version().as_deref() == Some("v1.0-3-g0123456"));
+
+	// Git before 2.32 does not support `%(describe)`, and leaves it as is.
+	let data = "node: 0123456789abcdef0123456789abcdef01234567\ndescribe-name: %(describe:tags=true)\n";
+	let_assert!(Some(archival) = Archival::parse(data));
+	assert!(archival.describe_name == None);
+	assert!(archival.version().as_deref() == Some("0123456"));
+
+	assert!(Archival::parse("node: $Format:%H$\ndescribe-name: $Format:%(describe:tags=true)$\n") == None);
+	assert!(Archival::parse("") == None);
+}
@@ -0,0 +1,111 @@
+//! In-process cache for git output.
+//!
+//! Crates often expand several macros of `git-version`, and IDEs like rust-analyzer expand macros again on nearly every keystroke.
+//! To avoid spawning the same git processes over and over, git output is cached for repositories that use [`Repo::with_cache()`],
+//! and for all repositories when macros are expanded by rust-analyzer.
+//! A cached result is reused as long as a cheap snapshot of the git metadata has not changed.
+//!
+//! Edits to tracked files do not change the git metadata, so the dirty state can lag behind until the next commit or `git add`.
+//! For a single compilation by `rustc` that does not matter, since the compiler process does not outlive the build.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::git::Repo;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// A snapshot of the modification time and size of the files that change with the git state.
+type Fingerprint = Vec<Option<(SystemTime, u64)>>;
+
+struct Entry {
+	fingerprint: Fingerprint,
+	output: Result<String, String>,
+}
+
+static ENTRIES: LazyLock<Mutex<HashMap<String, Entry>>> = LazyLock::new(Default::default);
+static GIT_DIRS: LazyLock<Mutex<HashMap<String, PathBuf>>> = LazyLock::new(Default::default);
+
+/// Check if git output should be cached, given whether the repository asked for it.
+///
+/// The output is also cached when running inside rust-analyzer.
+/// It can be forced on or off by setting `GIT_VERSION_CACHE` to `1` or `0`.
+pub fn enabled(requested: bool) -> bool {
+	static FORCED: OnceLock<Option<bool>> = OnceLock::new();
+	static RUST_ANALYZER: OnceLock<bool> = OnceLock::new();
+	let forced = FORCED.get_or_init(|| match std::env::var("GIT_VERSION_CACHE").as_deref() {
+		Ok("1") => Some(true),
+		Ok("0") => Some(false),
+		_ => None,
+	});
+	forced.unwrap_or_else(|| requested || *RUST_ANALYZER.get_or_init(is_rust_analyzer))
+}
+
+/// Check if the macros are being expanded by rust-analyzer.
+fn is_rust_analyzer() -> bool {
+	if std::env::var_os("RUST_ANALYZER_INTERNALS_DO_NOT_USE").is_some() {
+		return true;
+	}
+	std::env::current_exe()
+		.ok()
+		.and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().contains("rust-analyzer")))
+		.unwrap_or(false)
+}
+
+/// Get the cached output for `key`, or run `run` and cache the output.
+///
+/// The output is only reused while the git state of `repo` is unchanged.
+pub fn get_or_run(key: String, repo: &Repo, run: impl FnOnce() -> Result<String, String>) -> Result<String, String> {
+	let Some(fingerprint) = fingerprint(repo) else {
+		return run();
+	};
+
+	if let Some(entry) = ENTRIES.lock().unwrap().get(&key) {
+		if entry.fingerprint == fingerprint {
+			return entry.output.clone();
+		}
+	}
+
+	let output = run();
+	// Do not cache failures caused by the time budget.
+	if !repo.deadline_passed() {
+		ENTRIES.lock().unwrap().insert(key, Entry { fingerprint, output: output.clone() });
+	}
+	output
+}
+
+/// Take a snapshot of the git state of `repo`.
+fn fingerprint(repo: &Repo) -> Option<Fingerprint> {
+	let git_dir = cached_git_dir(repo)?;
+	Some(crate::git::state_files(&git_dir).iter().map(|file| {
+		let metadata = std::fs::metadata(file).ok()?;
+		Some((metadata.modified().ok()?, metadata.len()))
+	}).collect())
+}
+
+/// Get the git directory of `repo`, running git only the first time.
+fn cached_git_dir(repo: &Repo) -> Option<PathBuf> {
+	// The command line identifies the directory and environment, but not the deadline of the repository.
+	let key = crate::git::command_line(&repo.command());
+	if let Some(git_dir) = GIT_DIRS.lock().unwrap().get(&key) {
+		return Some(git_dir.clone());
+	}
+	let git_dir = repo.git_dir_uncached().ok()?;
+	GIT_DIRS.lock().unwrap().insert(key, git_dir.clone());
+	Some(git_dir)
+}
+
+#[test]
+fn test_get_or_run() {
+	use assert2::assert;
+
+	let repo = Repo::new(env!("CARGO_MANIFEST_DIR"));
+	let mut runs = 0;
+	let mut run = || {
+		runs += 1;
+		Ok("v1.2.3".to_owned())
+	};
+	assert!(get_or_run("test_get_or_run".into(), &repo, &mut run) == Ok("v1.2.3".into()));
+	assert!(get_or_run("test_get_or_run".into(), &repo, &mut run) == Ok("v1.2.3".into()));
+	assert!(runs == 1);
+}
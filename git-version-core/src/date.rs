@@ -0,0 +1,181 @@
+//! Formatting of dates that does not depend on the time zone or locale of the machine doing the build.
+//!
+//! Dates are shown in UTC unless [`TimeZone::Local`] is requested explicitly,
+//! and names of months and weekdays are always in English.
+
+/// The time zone to show a date in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TimeZone {
+	/// Coordinated Universal Time.
+	#[default]
+	Utc,
+
+	/// The local time zone of the machine doing the build, as used by git.
+	Local,
+}
+
+/// A point in time, together with the offset of the time zone it is shown in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Date {
+	/// The number of seconds since the Unix epoch.
+	pub timestamp: i64,
+
+	/// The offset of the time zone in seconds east of UTC.
+	pub offset: i32,
+}
+
+const MONTHS: [&str; 12] = [
+	"January", "February", "March", "April", "May", "June",
+	"July", "August", "September", "October", "November", "December",
+];
+
+const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+impl Date {
+	/// Format the date using a subset of the `strftime` syntax.
+	///
+	/// The supported conversions are:
+	/// `%Y` (year), `%m` (month), `%d` (day), `%H` (hour), `%M` (minute), `%S` (second),
+	/// `%b` and `%B` (abbreviated and full month name), `%a` and `%A` (abbreviated and full weekday name),
+	/// `%z` (offset like `+0100`), `%s` (Unix timestamp), `%F` (same as `%Y-%m-%d`), `%T` (same as `%H:%M:%S`) and `%%`.
+	pub fn format(&self, format: &str) -> Result<String, String> {
+		let local = self.timestamp + i64::from(self.offset);
+		let days = local.div_euclid(86400);
+		let seconds = local.rem_euclid(86400);
+		let (year, month, day) = civil_from_days(days);
+		let (hour, minute, second) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+		let weekday = WEEKDAYS[(days + 4).rem_euclid(7) as usize];
+		let month_name = MONTHS[month as usize - 1];
+
+		let mut output = String::new();
+		let mut chars = format.chars();
+		while let Some(c) = chars.next() {
+			if c != '%' {
+				output.push(c);
+				continue;
+			}
+			match chars.next() {
+				Some('Y') => output.push_str(&format!("{:04}", year)),
+				Some('m') => output.push_str(&format!("{:02}", month)),
+				Some('d') => output.push_str(&format!("{:02}", day)),
+				Some('H') => output.push_str(&format!("{:02}", hour)),
+				Some('M') => output.push_str(&format!("{:02}", minute)),
+				Some('S') => output.push_str(&format!("{:02}", second)),
+				Some('b') => output.push_str(&month_name[..3]),
+				Some('B') => output.push_str(month_name),
+				Some('a') => output.push_str(&weekday[..3]),
+				Some('A') => output.push_str(weekday),
+				Some('z') => {
+					let sign = if self.offset < 0 { '-' } else { '+' };
+					let offset = self.offset.unsigned_abs() / 60;
+					output.push_str(&format!("{}{:02}{:02}", sign, offset / 60, offset % 60));
+				}
+				Some('s') => output.push_str(&self.timestamp.to_string()),
+				Some('F') => output.push_str(&format!("{:04}-{:02}-{:02}", year, month, day)),
+				Some('T') => output.push_str(&format!("{:02}:{:02}:{:02}", hour, minute, second)),
+				Some('%') => output.push('%'),
+				Some(c) => return Err(format!("unsupported conversion `%{}` in date format: {:?}", c, format)),
+				None => return Err(format!("incomplete conversion at end of date format: {:?}", format)),
+			}
+		}
+		Ok(output)
+	}
+}
+
+/// Get the timestamp from the `SOURCE_DATE_EPOCH` environment variable, if it is set.
+///
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+/// An empty value counts as not set.
+pub fn source_date_epoch() -> Result<Option<i64>, String> {
+	parse_source_date_epoch(std::env::var_os("SOURCE_DATE_EPOCH").as_deref())
+}
+
+fn parse_source_date_epoch(value: Option<&std::ffi::OsStr>) -> Result<Option<i64>, String> {
+	let Some(value) = value.filter(|x| !x.is_empty()) else {
+		return Ok(None);
+	};
+	value.to_str()
+		.filter(|x| x.bytes().all(|x| x.is_ascii_digit()))
+		.and_then(|x| x.parse().ok())
+		.map(Some)
+		.ok_or_else(|| format!("SOURCE_DATE_EPOCH is not a valid Unix timestamp: {:?}", value))
+}
+
+/// Parse a time zone offset like `+0100` or `-0530` to seconds east of UTC.
+pub fn parse_offset(offset: &str) -> Option<i32> {
+	let (sign, digits) = match offset.as_bytes().first()? {
+		b'+' => (1, &offset[1..]),
+		b'-' => (-1, &offset[1..]),
+		_ => return None,
+	};
+	if digits.len() != 4 || !digits.bytes().all(|x| x.is_ascii_digit()) {
+		return None;
+	}
+	let hours: i32 = digits[..2].parse().ok()?;
+	let minutes: i32 = digits[2..].parse().ok()?;
+	Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Convert a number of days since the Unix epoch to a (year, month, day) date in the proleptic Gregorian calendar.
+///
+/// This is the `civil_from_days` algorithm by Howard Hinnant.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+	let days = days + 719468;
+	let era = days.div_euclid(146097);
+	let day_of_era = days.rem_euclid(146097);
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let shifted_month = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+	let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+	let year = year_of_era + era * 400 + i64::from(month <= 2);
+	(year, month, day)
+}
+
+#[test]
+fn test_format() {
+	use assert2::{assert, let_assert};
+
+	let date = Date { timestamp: 0, offset: 0 };
+	assert!(date.format("%F %T %z") == Ok("1970-01-01 00:00:00 +0000".into()));
+	assert!(date.format("%a %A %b %B 100%%") == Ok("Thu Thursday Jan January 100%".into()));
+
+	// 2024-02-29 23:30:00 UTC, a leap day.
+	let date = Date { timestamp: 1709249400, offset: 0 };
+	assert!(date.format("%Y-%m-%d %H:%M:%S") == Ok("2024-02-29 23:30:00".into()));
+	let date = Date { offset: 5400, ..date };
+	assert!(date.format("%F %T %z %a %s") == Ok("2024-03-01 01:00:00 +0130 Fri 1709249400".into()));
+	let date = Date { offset: -3600, ..date };
+	assert!(date.format("%F %T %z") == Ok("2024-02-29 22:30:00 -0100".into()));
+
+	let date = Date { timestamp: -1, offset: 0 };
+	assert!(date.format("%F %T %A") == Ok("1969-12-31 23:59:59 Wednesday".into()));
+
+	let_assert!(Err(e) = date.format("%Q"));
+	assert!(e.contains("`%Q`"));
+	assert!(let Err(_) = date.format("%Y%"));
+}
+
+#[test]
+fn test_parse_offset() {
+	use assert2::assert;
+
+	assert!(parse_offset("+0000") == Some(0));
+	assert!(parse_offset("+0130") == Some(5400));
+	assert!(parse_offset("-0800") == Some(-28800));
+	assert!(parse_offset("0100") == None);
+	assert!(parse_offset("+01:00") == None);
+	assert!(parse_offset("") == None);
+}
+
+#[test]
+fn test_parse_source_date_epoch() {
+	use assert2::assert;
+	use std::ffi::OsStr;
+
+	assert!(parse_source_date_epoch(None) == Ok(None));
+	assert!(parse_source_date_epoch(Some(OsStr::new(""))) == Ok(None));
+	assert!(parse_source_date_epoch(Some(OsStr::new("1700000000"))) == Ok(Some(1700000000)));
+	assert!(let Err(_) = parse_source_date_epoch(Some(OsStr::new("-1"))));
+	assert!(let Err(_) = parse_source_date_epoch(Some(OsStr::new("yesterday"))));
+}
@@ -0,0 +1,146 @@
+//! Answer git queries with gitoxide instead of running the `git` binary.
+//!
+//! Only the queries and `git describe` flags that gitoxide supports with the same output as git are handled here.
+//! For anything else, the functions return `None`, and [`Repo`](crate::git::Repo) runs the `git` binary as usual.
+
+use std::path::{Path, PathBuf};
+
+use gix::commit::describe::SelectRef;
+
+/// The `git describe` flags that can be handled by gitoxide.
+struct DescribeArgs {
+	always: bool,
+	long: bool,
+	tags: bool,
+
+	/// The dirty suffix, without the leading `-`.
+	dirty: Option<String>,
+}
+
+impl DescribeArgs {
+	/// Parse the flags for `git describe`, or return `None` if any flag is not supported.
+	fn parse<I, S>(args: I) -> Option<Self>
+	where
+		I: IntoIterator<Item = S>,
+		S: AsRef<std::ffi::OsStr>,
+	{
+		let mut parsed = Self { always: false, long: false, tags: false, dirty: None };
+		for arg in args {
+			match arg.as_ref().to_str()? {
+				"--always" => parsed.always = true,
+				"--long" => parsed.long = true,
+				"--tags" => parsed.tags = true,
+				"--dirty" => parsed.dirty = Some("dirty".into()),
+				// Gitoxide always separates the suffix with a `-`, so other suffixes are left to git.
+				arg => parsed.dirty = Some(arg.strip_prefix("--dirty=-")?.into()),
+			}
+		}
+		Some(parsed)
+	}
+}
+
+/// Open the repository containing a directory.
+fn open(dir: &Path) -> Result<gix::Repository, String> {
+	gix::discover(dir).map_err(|e| format!("Failed to open git repository at {:?}: {}", dir, e))
+}
+
+/// Run the equivalent of `git describe` with the given flags.
+pub fn describe<I, S>(dir: &Path, args: I) -> Option<Result<String, String>>
+where
+	I: IntoIterator<Item = S>,
+	S: AsRef<std::ffi::OsStr>,
+{
+	let args = DescribeArgs::parse(args)?;
+	Some(describe_parsed(dir, &args))
+}
+
+fn describe_parsed(dir: &Path, args: &DescribeArgs) -> Result<String, String> {
+	let repo = open(dir)?;
+	let commit = repo.head_commit().map_err(|e| format!("Failed to find the commit of HEAD: {}", e))?;
+	let names = if args.tags { SelectRef::AllTags } else { SelectRef::AnnotatedTags };
+	let resolution = commit.describe()
+		.names(names)
+		.id_as_fallback(args.always)
+		.try_resolve()
+		.map_err(|e| format!("Failed to describe HEAD: {}", e))?
+		.ok_or_else(|| String::from("No names found, cannot describe anything."))?;
+	let mut format = resolution.format_with_dirty_suffix(args.dirty.clone())
+		.map_err(|e| format!("Failed to describe HEAD: {}", e))?;
+	format.long = args.long;
+	Ok(format.to_string())
+}
+
+/// Get the git directory of the repository containing a directory.
+pub fn git_dir(dir: &Path) -> Result<PathBuf, String> {
+	Ok(open(dir)?.git_dir().to_owned())
+}
+
+/// Get the paths of all submodules that are checked out, recursively.
+///
+/// Returns `None` if the directory is not the root of the worktree,
+/// since `git submodule status` gives paths relative to the directory it runs in.
+pub fn submodules(dir: &Path) -> Option<Result<Vec<String>, String>> {
+	let repo = open(dir).ok()?;
+	let is_root = repo.workdir()?.canonicalize().ok()? == dir.canonicalize().ok()?;
+	if !is_root {
+		return None;
+	}
+	let mut paths = Vec::new();
+	Some(collect_submodules(&repo, "", &mut paths).map(|()| paths))
+}
+
+fn collect_submodules(repo: &gix::Repository, prefix: &str, paths: &mut Vec<String>) -> Result<(), String> {
+	let Some(submodules) = repo.submodules().map_err(|e| format!("Failed to read submodules: {}", e))? else {
+		return Ok(());
+	};
+	for submodule in submodules {
+		let path = submodule.path().map_err(|e| format!("Failed to read submodule path: {}", e))?;
+		let path = format!("{}{}", prefix, path);
+		// Like `git submodule status`, skip submodules that are not checked out.
+		let Some(repo) = submodule.open().map_err(|e| format!("Failed to open submodule {}: {}", path, e))? else {
+			continue;
+		};
+		paths.push(path.clone());
+		collect_submodules(&repo, &format!("{}/", path), paths)?;
+	}
+	Ok(())
+}
+
+#[test]
+fn test_describe_matches_git() {
+	use assert2::{assert, let_assert};
+	use crate::git::{test_git, Repo};
+
+	let_assert!(Ok(tempdir) = tempfile::tempdir());
+	let repo = Repo::new(tempdir.path());
+	let check = |args: &[&str]| {
+		let describe_args = std::iter::once("describe").chain(args.iter().copied()).collect::<Vec<_>>();
+		let expected = test_git(&repo, &describe_args);
+		let_assert!(Some(actual) = describe(tempdir.path(), args));
+		assert!(actual.is_ok() == expected.is_ok(), "{:?}: {:?} != {:?}", args, actual, expected);
+		if expected.is_ok() {
+			assert!(actual == expected, "{:?}", args);
+		}
+	};
+
+	let_assert!(Ok(_) = test_git(&repo, &["init", "--quiet"]));
+	let_assert!(Ok(_) = test_git(&repo, &["commit", "--quiet", "--allow-empty", "-m", "one"]));
+	check(&["--always"]);
+	check(&[]);
+
+	let_assert!(Ok(_) = test_git(&repo, &["tag", "-a", "-m", "v1.0", "v1.0"]));
+	check(&["--always", "--dirty=-modified"]);
+	check(&["--long"]);
+
+	let_assert!(Ok(_) = test_git(&repo, &["commit", "--quiet", "--allow-empty", "-m", "two"]));
+	let_assert!(Ok(_) = test_git(&repo, &["tag", "v1.1-light"]));
+	check(&["--always", "--dirty=-modified"]);
+	check(&["--tags", "--dirty"]);
+
+	let_assert!(Ok(()) = std::fs::write(tempdir.path().join("file.txt"), "one"));
+	let_assert!(Ok(_) = test_git(&repo, &["add", "file.txt"]));
+	check(&["--always", "--dirty=-modified"]);
+
+	assert!(let None = describe(tempdir.path(), ["--match=v*"]));
+	assert!(let None = describe(tempdir.path(), ["--dirty=+modified"]));
+}
@@ -0,0 +1,25 @@
+//! Core logic of [`git-version`](https://docs.rs/git-version), usable outside of procedural macros.
+//!
+//! This crate contains everything `git-version` does that does not involve generating code:
+//! running git, interpreting its output and transforming the resulting strings.
+//! It can be used from build scripts, release tooling or `xtask` binaries
+//! to get exactly the same results as the macros of `git-version`.
+//!
+//! ```
+//! use git_version_core::git;
+//!
+//! let version = git::Repo::new(".").describe(["--always", "--dirty=-modified"]);
+//! ```
+
+pub mod archival;
+mod cache;
+pub mod date;
+pub mod git;
+#[cfg(feature = "gix")]
+mod gitoxide;
+pub mod package;
+pub mod report;
+pub mod target;
+pub mod template;
+pub mod transform;
+pub mod workspace;
@@ -0,0 +1,76 @@
+//! Version control information of packaged crates.
+//!
+//! When a crate is packaged with `cargo package` or `cargo publish`,
+//! Cargo records the commit it was packaged from in a `.cargo_vcs_info.json` file.
+//! Sources extracted from such a package have no git repository, but this file is still there.
+
+use std::path::Path;
+
+/// The contents of a `.cargo_vcs_info.json` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VcsInfo {
+	/// The full hash of the commit the crate was packaged from.
+	pub sha1: String,
+
+	/// If true, the crate was packaged from a dirty worktree (with `--allow-dirty`).
+	pub dirty: bool,
+}
+
+impl VcsInfo {
+	/// Read the `.cargo_vcs_info.json` file in the given directory, if there is one.
+	pub fn read(manifest_dir: &Path) -> Option<Self> {
+		let data = std::fs::read_to_string(manifest_dir.join(".cargo_vcs_info.json")).ok()?;
+		Self::parse(&data)
+	}
+
+	/// Parse the contents of a `.cargo_vcs_info.json` file.
+	///
+	/// The file is written by Cargo in a fixed format, so only the fields needed here are looked up,
+	/// rather than parsing it as arbitrary JSON.
+	pub fn parse(data: &str) -> Option<Self> {
+		let sha1 = json_value(data, "sha1")?
+			.strip_prefix('"')?
+			.split('"')
+			.next()?;
+		if sha1.is_empty() || !sha1.bytes().all(|x| x.is_ascii_hexdigit()) {
+			return None;
+		}
+		let dirty = json_value(data, "dirty").is_some_and(|x| x.starts_with("true"));
+		Some(Self { sha1: sha1.to_owned(), dirty })
+	}
+
+	/// Get the commit hash abbreviated to 7 characters, like the default of `git describe`.
+	pub fn short_sha1(&self) -> &str {
+		self.abbreviated_sha1(7)
+	}
+
+	/// Get the commit hash abbreviated to the given number of characters.
+	pub fn abbreviated_sha1(&self, len: usize) -> &str {
+		&self.sha1[..self.sha1.len().min(len)]
+	}
+}
+
+/// Get the text following the `"key":` of a JSON object member.
+fn json_value<'a>(data: &'a str, key: &str) -> Option<&'a str> {
+	let (_, rest) = data.split_once(&format!("\"{}\"", key))?;
+	let rest = rest.trim_start().strip_prefix(':')?;
+	Some(rest.trim_start())
+}
+
+#[test]
+fn test_parse() {
+	use assert2::{assert, let_assert};
+
+	let data = "{\n  \"git\": {\n    \"sha1\": \"0123456789abcdef0123456789abcdef01234567\"\n  },\n  \"path_in_vcs\": \"git-version\"\n}";
+	let_assert!(Some(info) = VcsInfo::parse(data));
+	assert!(info.short_sha1() == "0123456");
+	assert!(info.abbreviated_sha1(12) == "0123456789ab");
+	assert!(info.abbreviated_sha1(100) == info.sha1);
+	assert!(!info.dirty);
+
+	let data = r#"{"git":{"sha1":"abcdef0123","dirty":true},"path_in_vcs":""}"#;
+	assert!(VcsInfo::parse(data) == Some(VcsInfo { sha1: "abcdef0123".into(), dirty: true }));
+
+	assert!(VcsInfo::parse(r#"{"path_in_vcs":""}"#) == None);
+	assert!(VcsInfo::parse(r#"{"git":{"sha1":"not a hash"}}"#) == None);
+}
@@ -0,0 +1,146 @@
+//! Machine-readable reports of macro expansions, for auditing which git state was embedded in a build.
+//!
+//! Reporting is enabled by the `GIT_VERSION_REPORT` environment variable.
+//! Every expansion appends one line of JSON to the report file (NDJSON), so reports of parallel builds can be combined.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The report of a single macro expansion.
+#[derive(Debug, Clone)]
+pub struct Record<'a> {
+	/// The name of the crate being compiled.
+	pub crate_name: &'a str,
+
+	/// The name of the macro, such as `git_version`.
+	pub macro_name: &'a str,
+
+	/// The arguments of the macro, as written in the source code.
+	pub inputs: &'a str,
+
+	/// The value the macro expanded to, or the error message if the expansion failed.
+	pub output: Result<&'a str, &'a str>,
+
+	/// If true, a fallback was used instead of the output of git.
+	pub fallback_used: bool,
+
+	/// The time the expansion took.
+	pub duration: Duration,
+}
+
+impl Record<'_> {
+	/// Render the record as a single line of JSON, without a trailing newline.
+	pub fn to_json(&self) -> String {
+		let (output, error) = match self.output {
+			Ok(output) => (json_string(output), "null".to_owned()),
+			Err(error) => ("null".to_owned(), json_string(error)),
+		};
+		format!(
+			"{{\"crate\":{},\"macro\":{},\"inputs\":{},\"output\":{},\"error\":{},\"fallback_used\":{},\"duration_ms\":{:.3}}}",
+			json_string(self.crate_name),
+			json_string(self.macro_name),
+			json_string(self.inputs),
+			output,
+			error,
+			self.fallback_used,
+			self.duration.as_secs_f64() * 1000.0,
+		)
+	}
+}
+
+/// Get the path of the report file, if reporting is enabled.
+///
+/// If `GIT_VERSION_REPORT` is `1`, the report is written to `git-version-report.ndjson` in the target directory,
+/// which is taken from `CARGO_TARGET_DIR`, or `target` in the current directory.
+/// Cargo runs the compiler in the root of the workspace, so that is the usual target directory.
+/// Any other non-empty value except `0` is used as the path of the report file.
+pub fn path() -> Option<PathBuf> {
+	match std::env::var_os("GIT_VERSION_REPORT")? {
+		x if x.is_empty() || x == "0" => None,
+		x if x == "1" => {
+			let target_dir = std::env::var_os("CARGO_TARGET_DIR").map_or_else(|| PathBuf::from("target"), PathBuf::from);
+			Some(target_dir.join("git-version-report.ndjson"))
+		}
+		x => Some(PathBuf::from(x)),
+	}
+}
+
+/// Append a record to the report file.
+///
+/// The line is written with a single write to a file opened in append mode,
+/// so lines of concurrent compiler processes do not get mixed up.
+pub fn append(path: &std::path::Path, record: &Record) -> Result<(), String> {
+	if let Some(parent) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+		std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+	}
+	let mut line = record.to_json();
+	line.push('\n');
+	std::fs::OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(path)
+		.and_then(|mut file| file.write_all(line.as_bytes()))
+		.map_err(|e| format!("Failed to write to {:?}: {}", path, e))
+}
+
+/// Quote a string for JSON.
+fn json_string(input: &str) -> String {
+	let mut output = String::with_capacity(input.len() + 2);
+	output.push('"');
+	for c in input.chars() {
+		match c {
+			'"' => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\n' => output.push_str("\\n"),
+			'\r' => output.push_str("\\r"),
+			'\t' => output.push_str("\\t"),
+			c if c < ' ' => output.push_str(&format!("\\u{:04x}", c as u32)),
+			c => output.push(c),
+		}
+	}
+	output.push('"');
+	output
+}
+
+#[test]
+fn test_to_json() {
+	use assert2::assert;
+
+	let record = Record {
+		crate_name: "my_crate",
+		macro_name: "git_version",
+		inputs: "prefix = \"git:\"",
+		output: Ok("git:v1.0\t\u{1}"),
+		fallback_used: false,
+		duration: Duration::from_micros(1500),
+	};
+	assert!(record.to_json() == concat!(
+		r#"{"crate":"my_crate","macro":"git_version","inputs":"prefix = \"git:\"","output":"git:v1.0\t\u0001","#,
+		r#""error":null,"fallback_used":false,"duration_ms":1.500}"#,
+	));
+
+	let record = Record { output: Err("git failed"), fallback_used: true, ..record };
+	assert!(record.to_json().contains(r#""output":null,"error":"git failed","fallback_used":true"#));
+}
+
+#[test]
+fn test_append() {
+	use assert2::{assert, let_assert};
+
+	let_assert!(Ok(tempdir) = tempfile::tempdir());
+	let path = tempdir.path().join("target/report.ndjson");
+	let record = Record {
+		crate_name: "a",
+		macro_name: "b",
+		inputs: "",
+		output: Ok("c"),
+		fallback_used: false,
+		duration: Duration::ZERO,
+	};
+	let_assert!(Ok(()) = append(&path, &record));
+	let_assert!(Ok(()) = append(&path, &record));
+	let_assert!(Ok(data) = std::fs::read_to_string(&path));
+	assert!(data.lines().count() == 2);
+	assert!(data.lines().all(|line| line == record.to_json()));
+}
@@ -0,0 +1,113 @@
+//! Detect the kind of Cargo target being compiled.
+
+use std::path::Path;
+
+/// The kind of a Cargo target.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TargetKind {
+	/// A library, including its unit tests.
+	Lib,
+
+	/// A binary.
+	Bin,
+
+	/// An example.
+	Example,
+
+	/// An integration test.
+	Test,
+
+	/// A benchmark.
+	Bench,
+}
+
+impl TargetKind {
+	/// All target kinds.
+	pub const ALL: [Self; 5] = [Self::Lib, Self::Bin, Self::Example, Self::Test, Self::Bench];
+
+	/// Get the name of the target kind, as used in `Cargo.toml`.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Lib => "lib",
+			Self::Bin => "bin",
+			Self::Example => "example",
+			Self::Test => "test",
+			Self::Bench => "bench",
+		}
+	}
+
+	/// Get a target kind by its name, as used in `Cargo.toml`.
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|x| x.name() == name)
+	}
+
+	/// Detect the kind of target being compiled from the environment variables that Cargo sets for the compiler.
+	///
+	/// Cargo does not tell the compiler the kind of target directly, so this is a heuristic:
+	/// integration tests and benchmarks are recognized by `CARGO_TARGET_TMPDIR`,
+	/// binaries by `CARGO_BIN_NAME`, and examples and benchmarks by their location in the `examples` and `benches` directories.
+	/// Targets with a custom `path` in `Cargo.toml` outside of those directories are detected as tests or binaries.
+	/// Unit tests are compiled as part of their target, so they are never detected as [`TargetKind::Test`].
+	pub fn from_env() -> Self {
+		Self::detect(|key| std::env::var(key).ok())
+	}
+
+	fn detect(var: impl Fn(&str) -> Option<String>) -> Self {
+		let manifest_dir = var("CARGO_MANIFEST_DIR").unwrap_or_default();
+		let manifest_dir = Path::new(&manifest_dir);
+		let bin_name = var("CARGO_BIN_NAME");
+		let Some(name) = bin_name.clone().or_else(|| var("CARGO_CRATE_NAME")) else {
+			return Self::Lib;
+		};
+
+		if var("CARGO_TARGET_TMPDIR").is_some() {
+			if has_target_source(&manifest_dir.join("benches"), &name) {
+				Self::Bench
+			} else {
+				Self::Test
+			}
+		} else if has_target_source(&manifest_dir.join("examples"), &name) {
+			Self::Example
+		} else if bin_name.is_some() {
+			Self::Bin
+		} else {
+			Self::Lib
+		}
+	}
+}
+
+/// Check if `dir` contains the source of a target with the given name, using Cargo's automatic target discovery.
+///
+/// Crate names have `-` replaced by `_`, so both spellings are checked.
+fn has_target_source(dir: &Path, name: &str) -> bool {
+	[name.to_owned(), name.replace('_', "-")].iter().any(|name| {
+		dir.join(format!("{}.rs", name)).is_file() || dir.join(name).join("main.rs").is_file()
+	})
+}
+
+#[test]
+fn test_detect() {
+	use assert2::{assert, let_assert};
+
+	let_assert!(Ok(tempdir) = tempfile::tempdir());
+	let_assert!(Ok(()) = std::fs::create_dir_all(tempdir.path().join("examples/my-demo")));
+	let_assert!(Ok(()) = std::fs::write(tempdir.path().join("examples/my-demo/main.rs"), ""));
+	let_assert!(Ok(()) = std::fs::create_dir(tempdir.path().join("benches")));
+	let_assert!(Ok(()) = std::fs::write(tempdir.path().join("benches/speed.rs"), ""));
+
+	let manifest_dir = tempdir.path().to_string_lossy().into_owned();
+	let detect = |vars: &[(&str, &str)]| {
+		TargetKind::detect(|key| {
+			match key {
+				"CARGO_MANIFEST_DIR" => Some(manifest_dir.clone()),
+				key => vars.iter().find(|(x, _)| *x == key).map(|(_, value)| value.to_string()),
+			}
+		})
+	};
+	assert!(detect(&[("CARGO_CRATE_NAME", "my_crate")]) == TargetKind::Lib);
+	assert!(detect(&[("CARGO_CRATE_NAME", "my_crate"), ("CARGO_BIN_NAME", "my-crate")]) == TargetKind::Bin);
+	assert!(detect(&[("CARGO_CRATE_NAME", "my_demo"), ("CARGO_BIN_NAME", "my-demo")]) == TargetKind::Example);
+	assert!(detect(&[("CARGO_CRATE_NAME", "my_demo")]) == TargetKind::Example);
+	assert!(detect(&[("CARGO_CRATE_NAME", "version"), ("CARGO_TARGET_TMPDIR", "/tmp")]) == TargetKind::Test);
+	assert!(detect(&[("CARGO_CRATE_NAME", "speed"), ("CARGO_TARGET_TMPDIR", "/tmp")]) == TargetKind::Bench);
+}
@@ -0,0 +1,115 @@
+//! Parsing of format strings with `{name}` placeholders.
+
+/// A piece of a parsed format string.
+#[derive(Debug, PartialEq)]
+pub enum Segment {
+	/// Literal text.
+	Text(String),
+
+	/// A `{name}` placeholder.
+	Placeholder(String),
+
+	/// A `{name:text}` placeholder, which stands for `text` if the value of `name` is not empty.
+	Conditional(String, String),
+}
+
+/// Parse a format string with `{name}` placeholders.
+///
+/// Literal braces can be written as `{{` and `}}`.
+/// Only the placeholders listed in `allowed` are accepted.
+pub fn parse(format: &str, allowed: &[&str]) -> Result<Vec<Segment>, String> {
+	parse_impl(format, allowed, false)
+}
+
+/// Parse a format string with `{name}` placeholders and `{name:text}` conditional placeholders.
+///
+/// A conditional placeholder stands for `text` if the value of `name` is not empty, and for nothing otherwise.
+pub fn parse_conditional(format: &str, allowed: &[&str]) -> Result<Vec<Segment>, String> {
+	parse_impl(format, allowed, true)
+}
+
+fn parse_impl(format: &str, allowed: &[&str], conditional: bool) -> Result<Vec<Segment>, String> {
+	let mut segments = Vec::new();
+	let mut text = String::new();
+	let mut chars = format.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'{' if chars.as_str().starts_with('{') => {
+				chars.next();
+				text.push('{');
+			}
+			'}' if chars.as_str().starts_with('}') => {
+				chars.next();
+				text.push('}');
+			}
+			'{' => {
+				let rest = chars.as_str();
+				let end = rest.find('}').ok_or_else(|| format!("unterminated placeholder in format string: {:?}", format))?;
+				let (name, condition_text) = match rest[..end].split_once(':') {
+					Some((name, text)) if conditional => (name, Some(text)),
+					_ => (&rest[..end], None),
+				};
+				if !allowed.contains(&name) {
+					let allowed: Vec<_> = allowed.iter().map(|x| format!("`{{{}}}`", x)).collect();
+					return Err(format!("unknown placeholder `{{{}}}`, expected one of: {}", name, allowed.join(", ")));
+				}
+				if !text.is_empty() {
+					segments.push(Segment::Text(std::mem::take(&mut text)));
+				}
+				segments.push(match condition_text {
+					Some(text) => Segment::Conditional(name.to_owned(), text.to_owned()),
+					None => Segment::Placeholder(name.to_owned()),
+				});
+				chars = rest[end + 1..].chars();
+			}
+			'}' => return Err(format!("unmatched `}}` in format string: {:?}", format)),
+			c => text.push(c),
+		}
+	}
+	if !text.is_empty() {
+		segments.push(Segment::Text(text));
+	}
+	Ok(segments)
+}
+
+/// Render a parsed format string, using `value` to get the value of each placeholder.
+pub fn render(segments: &[Segment], value: impl Fn(&str) -> String) -> String {
+	segments.iter()
+		.map(|segment| match segment {
+			Segment::Text(text) => text.clone(),
+			Segment::Placeholder(name) => value(name),
+			Segment::Conditional(name, text) if !value(name).is_empty() => text.clone(),
+			Segment::Conditional(..) => String::new(),
+		})
+		.collect()
+}
+
+#[test]
+fn test_parse() {
+	use assert2::{assert, let_assert};
+
+	let_assert!(Ok(segments) = parse("{cargo} ({git}) {{x}}", &["cargo", "git"]));
+	assert!(segments == [
+		Segment::Placeholder("cargo".into()),
+		Segment::Text(" (".into()),
+		Segment::Placeholder("git".into()),
+		Segment::Text(") {x}".into()),
+	]);
+	let_assert!(Err(e) = parse("{cargo}+{hash}", &["cargo", "git"]));
+	assert!(e == "unknown placeholder `{hash}`, expected one of: `{cargo}`, `{git}`");
+	assert!(let Err(_) = parse("{cargo", &["cargo"]));
+	assert!(let Err(_) = parse("cargo}", &["cargo"]));
+}
+
+#[test]
+fn test_render() {
+	use assert2::{assert, let_assert};
+
+	let_assert!(Ok(segments) = parse("{cargo} ({hash}) {{x}}", &["cargo", "hash"]));
+	assert!(render(&segments, |name| name.to_uppercase()) == "CARGO (HASH) {x}");
+
+	let_assert!(Ok(segments) = parse_conditional("{tag}{dirty:-dev}", &["tag", "dirty"]));
+	assert!(render(&segments, |name| if name == "tag" { "v1".into() } else { "-modified".into() }) == "v1-dev");
+	assert!(render(&segments, |name| if name == "tag" { "v1".into() } else { String::new() }) == "v1");
+	assert!(let Err(_) = parse("{dirty:-dev}", &["dirty"]));
+}
@@ -0,0 +1,224 @@
+//! Transformations applied to version strings and lists after they are retrieved from git.
+
+use crate::git::{DescribeFields, StatusEntry};
+
+/// How to report why the worktree is dirty.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DirtyCause {
+	/// The number of changed and untracked files, like `(3f,1u)`.
+	Counts,
+
+	/// The paths of up to this many dirty files.
+	Paths(usize),
+}
+
+/// What to do when the version is longer than `max_len`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Overflow {
+	/// Return an error.
+	#[default]
+	Error,
+
+	/// Truncate the version to `max_len` bytes.
+	Truncate,
+}
+
+/// Format a compact description of why the worktree is dirty, like `(3f,1u)` or `(src/lib.rs,+2)`.
+pub fn dirty_cause(entries: &[StatusEntry], cause: DirtyCause) -> String {
+	let mut parts = Vec::new();
+	match cause {
+		DirtyCause::Counts => {
+			let untracked = entries.iter().filter(|x| x.untracked).count();
+			let changed = entries.len() - untracked;
+			if changed > 0 {
+				parts.push(format!("{}f", changed));
+			}
+			if untracked > 0 {
+				parts.push(format!("{}u", untracked));
+			}
+		}
+		DirtyCause::Paths(max) => {
+			parts.extend(entries.iter().take(max).map(|x| x.path.clone()));
+			if entries.len() > max {
+				parts.push(format!("+{}", entries.len() - max));
+			}
+		}
+	}
+	if parts.is_empty() {
+		String::new()
+	} else {
+		format!("({})", parts.join(","))
+	}
+}
+
+/// Limit the length of a version string to `max_len` bytes.
+///
+/// When truncating, the string is cut at the last character boundary that fits.
+pub fn limit_len(value: String, max_len: usize, overflow: Overflow) -> Result<String, String> {
+	if value.len() <= max_len {
+		return Ok(value);
+	}
+	match overflow {
+		Overflow::Error => Err(format!("version `{}` is {} bytes long, which exceeds `max_len = {}`", value, value.len(), max_len)),
+		Overflow::Truncate => {
+			let end = (0..=max_len).rev().find(|&i| value.is_char_boundary(i)).unwrap_or(0);
+			Ok(value[..end].to_owned())
+		}
+	}
+}
+
+/// Turn the components of the output of `git describe` into a valid SemVer version.
+///
+/// An exact tag of a clean worktree, like `v1.2.3`, becomes `1.2.3`.
+/// Otherwise, the number of commits since the tag becomes a `dev` pre-release,
+/// and the hash and the dirty state become build metadata, like `1.2.3-dev.4+gabc1234.dirty`.
+///
+/// A leading `v` is removed from the tag, and a missing minor or patch number is taken to be `0`.
+/// If there is no tag, or the tag does not start with a version number, the version is `0.0.0-dev`,
+/// with the identifiers of the tag (if any) before `dev`.
+/// Characters that SemVer does not allow are removed from the identifiers, and so are leading zeros from numbers.
+pub fn semver(fields: &DescribeFields) -> String {
+	let tag = fields.tag.as_deref().unwrap_or_default();
+	let tag = tag.strip_prefix(['v', 'V']).unwrap_or(tag);
+	let (tag, tag_build) = tag.split_once('+').unwrap_or((tag, ""));
+	let (core, tag_pre) = tag.split_once('-').unwrap_or((tag, ""));
+
+	let numbers: Vec<&str> = core.split('.').collect();
+	let is_version = numbers.len() <= 3 && numbers.iter().all(|x| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit()));
+	let (core, mut pre) = if is_version {
+		let mut numbers: Vec<String> = numbers.iter().map(|x| strip_leading_zeros(x)).collect();
+		numbers.resize(3, "0".into());
+		(numbers.join("."), semver_identifiers(tag_pre, true))
+	} else {
+		("0.0.0".to_owned(), semver_identifiers(tag, true))
+	};
+	let mut build = semver_identifiers(tag_build, false);
+
+	if !is_version || fields.commits > 0 {
+		pre.push("dev".into());
+	}
+	if fields.commits > 0 {
+		pre.push(fields.commits.to_string());
+	}
+	if !is_version || fields.commits > 0 || fields.dirty {
+		build.extend(semver_identifiers(&format!("g{}", fields.sha), false));
+	}
+	if fields.dirty {
+		build.push("dirty".into());
+	}
+
+	let mut version = core;
+	if !pre.is_empty() {
+		version.push('-');
+		version.push_str(&pre.join("."));
+	}
+	if !build.is_empty() {
+		version.push('+');
+		version.push_str(&build.join("."));
+	}
+	version
+}
+
+/// Split dot-separated SemVer identifiers, removing characters that are not allowed and dropping empty identifiers.
+///
+/// If `pre_release` is true, leading zeros are removed from numeric identifiers, since pre-release identifiers can not have them.
+fn semver_identifiers(input: &str, pre_release: bool) -> Vec<String> {
+	input.split('.')
+		.map(|x| x.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect::<String>())
+		.filter(|x| !x.is_empty())
+		.map(|x| if pre_release { strip_leading_zeros(&x) } else { x })
+		.collect()
+}
+
+/// Remove the leading zeros of a number, keeping a single `0`, or return other strings as is.
+fn strip_leading_zeros(input: &str) -> String {
+	if !input.bytes().all(|x| x.is_ascii_digit()) {
+		return input.to_owned();
+	}
+	match input.trim_start_matches('0') {
+		"" => "0".to_owned(),
+		x => x.to_owned(),
+	}
+}
+
+/// Sort and de-duplicate the entries of an array produced by a macro.
+///
+/// All macros that expand to an array use this, so their output does not depend on the order in which git reports things.
+/// Entries are sorted by the byte-wise order of their key, and only the first entry for each key is kept.
+pub fn sort_unique_by_key<T>(items: &mut Vec<T>, key: impl Fn(&T) -> &str) {
+	items.sort_by(|a, b| key(a).cmp(key(b)));
+	items.dedup_by(|a, b| key(a) == key(b));
+}
+
+/// Decode a hexadecimal string, like a commit hash, to bytes.
+///
+/// Returns `None` if the string has an odd length or contains anything other than hexadecimal digits.
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+	if !hex.len().is_multiple_of(2) || !hex.bytes().all(|x| x.is_ascii_hexdigit()) {
+		return None;
+	}
+	hex.as_bytes()
+		.chunks(2)
+		.map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+		.collect()
+}
+
+#[test]
+fn test_limit_len() {
+	use assert2::{assert, let_assert};
+
+	assert!(limit_len("v1.2.3".into(), 6, Overflow::Error) == Ok("v1.2.3".into()));
+	assert!(limit_len("v1.2.3".into(), 4, Overflow::Truncate) == Ok("v1.2".into()));
+	assert!(limit_len("v1.2-ä".into(), 6, Overflow::Truncate) == Ok("v1.2-".into()));
+	let_assert!(Err(e) = limit_len("v1.2.3".into(), 4, Overflow::Error));
+	assert!(e.contains("exceeds `max_len = 4`"));
+}
+
+#[test]
+fn test_dirty_cause() {
+	use assert2::assert;
+
+	let entry = |path: &str, untracked| StatusEntry { path: path.into(), untracked };
+	let entries = [entry("a.rs", false), entry("b.rs", false), entry("c.rs", false), entry("d.txt", true)];
+	assert!(dirty_cause(&entries, DirtyCause::Counts) == "(3f,1u)");
+	assert!(dirty_cause(&entries[..1], DirtyCause::Counts) == "(1f)");
+	assert!(dirty_cause(&entries, DirtyCause::Paths(2)) == "(a.rs,b.rs,+2)");
+	assert!(dirty_cause(&entries[..2], DirtyCause::Paths(2)) == "(a.rs,b.rs)");
+	assert!(dirty_cause(&[], DirtyCause::Counts) == "");
+}
+
+#[test]
+fn test_sort_unique_by_key() {
+	use assert2::assert;
+
+	let mut items = vec![("b", 1), ("a", 2), ("B", 3), ("a", 4)];
+	sort_unique_by_key(&mut items, |x| x.0);
+	assert!(items == [("B", 3), ("a", 2), ("b", 1)]);
+}
+
+#[test]
+fn test_decode_hex() {
+	use assert2::assert;
+
+	assert!(decode_hex("0123abCD") == Some(vec![0x01, 0x23, 0xab, 0xcd]));
+	assert!(decode_hex("") == Some(vec![]));
+	assert!(decode_hex("123") == None);
+	assert!(decode_hex("+1") == None);
+	assert!(decode_hex("zz") == None);
+}
+
+#[test]
+fn test_semver() {
+	use assert2::assert;
+
+	let fields = |tag: Option<&str>, commits, dirty| DescribeFields { tag: tag.map(Into::into), commits, sha: "abc1234".into(), dirty };
+	assert!(semver(&fields(Some("v1.2.3"), 0, false)) == "1.2.3");
+	assert!(semver(&fields(Some("v1.2.3"), 4, true)) == "1.2.3-dev.4+gabc1234.dirty");
+	assert!(semver(&fields(Some("v1.2.3"), 0, true)) == "1.2.3+gabc1234.dirty");
+	assert!(semver(&fields(Some("1.2"), 0, false)) == "1.2.0");
+	assert!(semver(&fields(Some("v01.02.3-rc.01+build_5"), 2, false)) == "1.2.3-rc.1.dev.2+build5.gabc1234");
+	assert!(semver(&fields(Some("release/stable"), 3, false)) == "0.0.0-releasestable.dev.3+gabc1234");
+	assert!(semver(&fields(Some("v1.2.3.4"), 0, false)) == "0.0.0-1.2.3.4.dev+gabc1234");
+	assert!(semver(&fields(None, 0, false)) == "0.0.0-dev+gabc1234");
+	assert!(semver(&fields(None, 0, true)) == "0.0.0-dev+gabc1234.dirty");
+}
@@ -0,0 +1,137 @@
+//! Members of a Cargo workspace, found by reading the `Cargo.toml` files without running Cargo.
+//!
+//! Only the parts of `Cargo.toml` needed to find the members are used:
+//! the `members` and `exclude` lists of the `workspace` table, and the `name` of the `package` table.
+//! Like Cargo, `members` are glob patterns, and `exclude` removes every member inside an excluded directory.
+
+use std::path::{Path, PathBuf};
+
+/// A package in a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+	/// The name of the package.
+	pub name: String,
+
+	/// The directory containing the `Cargo.toml` of the package.
+	pub dir: PathBuf,
+}
+
+/// Find the root of the workspace containing the package in the given directory.
+///
+/// This is the nearest directory, starting at `manifest_dir` itself, with a `Cargo.toml` that has a `workspace` table.
+/// If there is none, the package is its own workspace, and `manifest_dir` is returned.
+pub fn find_root(manifest_dir: &Path) -> PathBuf {
+	manifest_dir.ancestors()
+		.find(|dir| read_manifest(dir).is_ok_and(|manifest| manifest.contains_key("workspace")))
+		.unwrap_or(manifest_dir)
+		.to_owned()
+}
+
+/// Get the members of the workspace with the given root directory.
+///
+/// The package in the root directory is a member if the root `Cargo.toml` has a `package` table.
+/// Directories matching `members` without a `Cargo.toml` or without a `package` table are ignored,
+/// and so are directories inside a directory listed in `exclude`.
+pub fn members(root: &Path) -> Result<Vec<Member>, String> {
+	let manifest = read_manifest(root)?;
+
+	let mut dirs = vec![root.to_owned()];
+	if let Some(workspace) = manifest.get("workspace") {
+		let exclude: Vec<PathBuf> = string_array(workspace, "exclude")?.into_iter().map(|x| root.join(x)).collect();
+		for pattern in string_array(workspace, "members")? {
+			dirs.extend(expand(root, &pattern)?.into_iter().filter(|dir| !exclude.iter().any(|x| dir.starts_with(x))));
+		}
+	}
+
+	let mut members: Vec<Member> = Vec::new();
+	for dir in dirs {
+		let Ok(manifest) = read_manifest(&dir) else {
+			continue;
+		};
+		let Some(name) = manifest.get("package").and_then(|x| x.get("name")).and_then(|x| x.as_str()) else {
+			continue;
+		};
+		if !members.iter().any(|x| x.dir == dir) {
+			members.push(Member { name: name.to_owned(), dir });
+		}
+	}
+	Ok(members)
+}
+
+/// Read and parse the `Cargo.toml` in the given directory.
+fn read_manifest(dir: &Path) -> Result<toml::Table, String> {
+	let path = dir.join("Cargo.toml");
+	let data = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+	data.parse().map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+/// Get an array of strings of a TOML table, or an empty list if the key is missing.
+fn string_array(table: &toml::Value, key: &str) -> Result<Vec<String>, String> {
+	let Some(value) = table.get(key) else {
+		return Ok(Vec::new());
+	};
+	value.as_array()
+		.and_then(|array| array.iter().map(|x| x.as_str().map(normalize)).collect())
+		.ok_or_else(|| format!("`workspace.{}` must be an array of strings", key))
+}
+
+/// Normalize a path from `Cargo.toml` to use `/` as separator, without empty and `.` components.
+fn normalize(path: &str) -> String {
+	path.split(['/', '\\']).filter(|x| !x.is_empty() && *x != ".").collect::<Vec<_>>().join("/")
+}
+
+/// Get the directories that match a pattern of `members`, sorted by path.
+fn expand(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+	let root = root.to_str().ok_or_else(|| format!("Workspace root is not valid UTF-8: {:?}", root))?;
+	let full = format!("{}/{}", glob::Pattern::escape(root), pattern);
+	let paths = glob::glob(&full).map_err(|e| format!("Invalid pattern in `workspace.members`: {:?}: {}", pattern, e))?;
+	let mut dirs: Vec<PathBuf> = paths.flatten().filter(|x| x.is_dir()).collect();
+	dirs.sort();
+	Ok(dirs)
+}
+
+#[test]
+fn test_members() {
+	use assert2::{assert, let_assert};
+
+	let_assert!(Ok(tempdir) = tempfile::tempdir());
+	let root = tempdir.path();
+	let write = |path: &str, data: &str| {
+		let path = root.join(path);
+		let_assert!(Some(parent) = path.parent());
+		let_assert!(Ok(()) = std::fs::create_dir_all(parent));
+		let_assert!(Ok(()) = std::fs::write(path, data));
+	};
+	write("Cargo.toml", concat!(
+		"package.name = \"app\" # comment\n\n",
+		"[workspace]\nmembers = [\n\t\"crates/*\", # comment\n\t'./tool/',\n\t\"plugins/[ab]\",\n\t\"deep/**\",\n]\n",
+		"exclude = [\"crates/skip\", \"deep/skip\"]\n",
+		"[dependencies]\n",
+	));
+	write("crates/a/Cargo.toml", "[package]\nname = \"crate-a\"\n");
+	write("crates/b/Cargo.toml", "[package]\nname = 'crate-b'\n");
+	write("crates/skip/Cargo.toml", "[package]\nname = \"skip\"\n");
+	write("crates/not-a-crate/README.md", "");
+	write("crates/a/nested/Cargo.toml", "[package]\nname = \"nested\"\n");
+	write("tool/Cargo.toml", "package = { name = \"tool\" }\n");
+	write("plugins/a/Cargo.toml", "[package]\nname = \"plugin-a\"\n");
+	write("plugins/c/Cargo.toml", "[package]\nname = \"plugin-c\"\n");
+	write("deep/x/y/Cargo.toml", "[package]\nname = \"deep-y\"\n");
+	write("deep/skip/z/Cargo.toml", "[package]\nname = \"deep-z\"\n");
+
+	let_assert!(Ok(members) = members(root));
+	let names: Vec<_> = members.iter().map(|x| x.name.as_str()).collect();
+	assert!(names == ["app", "crate-a", "crate-b", "tool", "plugin-a", "deep-y"]);
+	assert!(members[1].dir == root.join("crates/a"));
+
+	assert!(find_root(&root.join("crates/a")) == root);
+	assert!(find_root(&root.join("crates/a/nested")) == root);
+	assert!(let Err(_) = self::members(&root.join("crates")));
+
+	write("broken/Cargo.toml", "[workspace]\nmembers = \"crates/*\"\n");
+	let_assert!(Err(e) = self::members(&root.join("broken")));
+	assert!(e.contains("`workspace.members` must be an array of strings"));
+	write("broken/Cargo.toml", "[workspace\n");
+	let_assert!(Err(e) = self::members(&root.join("broken")));
+	assert!(e.contains("Failed to parse"));
+}
@@ -1,15 +1,497 @@
-use syn::{LitStr, Expr, Ident};
+use proc_macro2::Span;
+use syn::{LitInt, LitStr, Expr, Ident};
+use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
 
+use git_version_core::date::TimeZone;
+use git_version_core::git::{self, Refs, Repo, Untracked};
+use git_version_core::target::TargetKind;
+use git_version_core::transform::{DirtyCause, Overflow};
+
 #[derive(Default)]
 pub struct Args {
+	pub names: Vec<Ident>,
 	pub git_args: Option<Punctuated<LitStr, Comma>>,
+	pub match_patterns: Option<Vec<LitStr>>,
+	pub exclude_patterns: Option<Vec<LitStr>>,
+	pub abbrev_len: Option<usize>,
+	pub dirty_suffix: Option<LitStr>,
+	pub broken_suffix: Option<LitStr>,
 	pub prefix: Option<Expr>,
 	pub suffix: Option<Expr>,
 	pub cargo_prefix: Option<Expr>,
 	pub cargo_suffix: Option<Expr>,
 	pub fallback: Option<Expr>,
+	pub pattern: Option<LitStr>,
+	pub since: Option<LitStr>,
+	pub max_len: Option<usize>,
+	pub overflow: Option<Overflow>,
+	pub combine_format: Option<LitStr>,
+	pub validate: Option<LitStr>,
+	pub replace: Option<Punctuated<Replacement, Comma>>,
+	pub dirty_cause: Option<DirtyCause>,
+	pub refs: Option<Refs>,
+	pub git_ref: Option<LitStr>,
+	pub env: Option<Vec<(String, String)>>,
+	pub untracked: Option<Untracked>,
+	pub target_suffix: Option<Vec<(TargetKind, LitStr)>>,
+	pub check: Option<Check>,
+	pub inherit_git_env: Option<bool>,
+	pub allow_unsafe_directory: Option<bool>,
+	pub static_ref: Option<bool>,
+	pub as_slice: Option<bool>,
+	pub budget_ms: Option<u64>,
+	pub key: Option<LitStr>,
+	pub remote: Option<LitStr>,
+	pub rules: Option<Punctuated<Rule, Comma>>,
+	pub format: Option<LitStr>,
+	pub semver: Option<bool>,
+	pub tz: Option<TimeZone>,
+	pub repo_select: Option<RepoSelect>,
+	pub post: Option<syn::Path>,
+	pub packaged_format: Option<LitStr>,
+	pub short: Option<usize>,
+	pub uppercase: Option<bool>,
+	pub max_count: Option<usize>,
+	pub detached: Option<Detached>,
+	pub env_override: Option<LitStr>,
+	pub sources: Option<Punctuated<SourceArg, Comma>>,
+	pub fallback_env: Option<LitStr>,
+	pub fallback_file: Option<LitStr>,
+	pub path: Option<LitStr>,
+	pub relative_to: Option<RelativeTo>,
+	pub require_clean: Option<RequireClean>,
+	pub dirty_exclude: Option<Punctuated<LitStr, Comma>>,
+	pub paths: Option<Punctuated<LitStr, Comma>>,
+	pub urls: Option<bool>,
+	pub tag: Option<bool>,
+	pub descendant: Option<bool>,
+	pub ignore_git_errors: Option<bool>,
+	pub out_of_sync: Option<Expr>,
+	pub overrides: Option<Vec<(LitStr, Vec<String>)>>,
+	pub track: Option<bool>,
+}
+
+/// What to do when the version does not match the `GIT_VERSION` environment variable.
+#[derive(Copy, Clone)]
+pub enum Check {
+	Warn,
+	Error,
+}
+
+/// When a dirty worktree fails the build, given by the `require_clean` argument.
+#[derive(Copy, Clone)]
+pub enum RequireClean {
+	/// In every build.
+	Always,
+
+	/// Only in builds without debug assertions, such as release builds.
+	Release,
+}
+
+/// What `git_branch!()` produces when `HEAD` is detached, given by the `detached` argument.
+pub enum Detached {
+	/// Report an error, or use the fallback if there is one.
+	Error,
+
+	/// Use the abbreviated commit hash.
+	ShortHash,
+
+	/// Use a fixed string.
+	Text(LitStr),
+}
+
+/// A place to get the version from, given in the `sources` argument.
+#[derive(Clone)]
+pub enum SourceArg {
+	/// The output of `git describe`.
+	Git,
+
+	/// The `.git_archival.txt` file of an archive made by `git archive`.
+	Archival,
+
+	/// The `.cargo_vcs_info.json` file of a package made by `cargo package`.
+	CargoVcsInfo,
+
+	/// An environment variable, given as `env("NAME")`.
+	Env(LitStr),
+
+	/// A file relative to the directory of the crate, given as `file("VERSION")`.
+	File(LitStr),
+
+	/// The CARGO_PKG_VERSION environment variable.
+	Cargo,
+
+	/// A fixed string.
+	Text(LitStr),
+}
+
+impl syn::parse::Parse for SourceArg {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		if input.peek(LitStr) {
+			return Ok(Self::Text(input.parse()?));
+		}
+		let ident: Ident = input.parse()?;
+		match ident.to_string().as_str() {
+			"git" => Ok(Self::Git),
+			"archival" => Ok(Self::Archival),
+			"cargo_vcs_info" => Ok(Self::CargoVcsInfo),
+			"cargo" => Ok(Self::Cargo),
+			"env" | "file" => {
+				let content;
+				syn::parenthesized!(content in input);
+				let value = content.parse()?;
+				let _: Option<Comma> = content.parse()?;
+				match ident == "env" {
+					true => Ok(Self::Env(value)),
+					false => Ok(Self::File(value)),
+				}
+			}
+			_ => Err(syn::Error::new_spanned(
+				ident,
+				"expected `git`, `archival`, `cargo_vcs_info`, `env(\"NAME\")`, `file(\"PATH\")`, `cargo` or a string literal",
+			)),
+		}
+	}
+}
+
+/// Where to start looking for the repository, given by the `relative_to` argument.
+#[derive(Copy, Clone)]
+pub enum RelativeTo {
+	Manifest,
+	Source,
+}
+
+/// Which repository to describe, given by the `repo` argument.
+#[derive(Copy, Clone)]
+pub enum RepoSelect {
+	Current,
+	Superproject,
+}
+
+/// A `("from", "to")` pair of the `replace` argument.
+pub struct Replacement {
+	pub from: LitStr,
+	pub to: LitStr,
+}
+
+impl syn::parse::Parse for Replacement {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let content;
+		syn::parenthesized!(content in input);
+		let from: LitStr = content.parse()?;
+		if from.value().is_empty() {
+			return Err(syn::Error::new_spanned(from, "the string to replace can not be empty"));
+		}
+		let _: Comma = content.parse()?;
+		let to = content.parse()?;
+		let _: Option<Comma> = content.parse()?;
+		Ok(Self { from, to })
+	}
+}
+
+/// Get the directory of the source file that invokes the macro.
+///
+/// This is `None` if the invocation does not come from a file on disk, such as code generated by another macro.
+fn source_dir() -> Option<std::path::PathBuf> {
+	let file = proc_macro::Span::call_site().local_file()?;
+	// The path is relative to the directory the compiler runs in, which is the root of the workspace for Cargo.
+	let file = std::env::current_dir().ok()?.join(file);
+	Some(file.parent()?.to_owned())
+}
+
+/// Parse a `{ KEY = VALUE, ... }` map, such as `{ KEY = "VALUE" }`.
+///
+/// Keys can be identifiers or string literals, and can only appear once.
+fn parse_map<T: syn::parse::Parse>(input: syn::parse::ParseStream) -> syn::Result<Vec<(String, Span, T)>> {
+	let content;
+	syn::braced!(content in input);
+	let mut map: Vec<(String, Span, T)> = Vec::new();
+	while !content.is_empty() {
+		let (key, span) = if content.peek(LitStr) {
+			let key: LitStr = content.parse()?;
+			(key.value(), key.span())
+		} else {
+			let key: Ident = content.parse()?;
+			(key.to_string(), key.span())
+		};
+		if map.iter().any(|(x, _, _)| *x == key) {
+			return Err(syn::Error::new(span, format!("`{}` can only appear once", key)));
+		}
+		let _: syn::token::Eq = content.parse()?;
+		map.push((key, span, content.parse()?));
+		if content.is_empty() {
+			break;
+		}
+		let _: Comma = content.parse()?;
+	}
+	Ok(map)
+}
+
+/// Parse a glob pattern or a `["PATTERN", ...]` list of them, for the `match` and `exclude` arguments.
+fn parse_patterns(input: syn::parse::ParseStream) -> syn::Result<Vec<LitStr>> {
+	let patterns: Vec<LitStr> = if input.peek(syn::token::Bracket) {
+		let content;
+		syn::bracketed!(content in input);
+		Punctuated::<LitStr, Comma>::parse_terminated(&content)?.into_iter().collect()
+	} else {
+		vec![input.parse()?]
+	};
+	if let Some(pattern) = patterns.iter().find(|x| x.value().is_empty()) {
+		return Err(syn::Error::new_spanned(pattern, "the pattern can not be empty"));
+	}
+	Ok(patterns)
+}
+
+/// Parse the map of the `env` argument.
+fn parse_env(input: syn::parse::ParseStream) -> syn::Result<Vec<(String, String)>> {
+	parse_map::<LitStr>(input)?
+		.into_iter()
+		.map(|(key, span, value)| {
+			if key.is_empty() || key.contains(['=', '\0']) {
+				return Err(syn::Error::new(span, "invalid environment variable name"));
+			}
+			Ok((key, value.value()))
+		})
+		.collect()
+}
+
+/// Parse the map of the `target_suffix` argument.
+fn parse_target_suffix(input: syn::parse::ParseStream) -> syn::Result<Vec<(TargetKind, LitStr)>> {
+	parse_map::<LitStr>(input)?
+		.into_iter()
+		.map(|(key, span, value)| {
+			let kind = TargetKind::from_name(&key)
+				.ok_or_else(|| syn::Error::new(span, "expected `lib`, `bin`, `example`, `test` or `bench`"))?;
+			Ok((kind, value))
+		})
+		.collect()
+}
+
+/// A `["ARG", ...]` list of arguments for git, as used in the map of the `overrides` argument.
+struct GitArgs(Punctuated<LitStr, Comma>);
+
+impl syn::parse::Parse for GitArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let content;
+		syn::bracketed!(content in input);
+		Ok(Self(Punctuated::parse_terminated(&content)?))
+	}
+}
+
+/// Parse the map of the `overrides` argument.
+fn parse_overrides(input: syn::parse::ParseStream) -> syn::Result<Vec<(LitStr, Vec<String>)>> {
+	Ok(parse_map::<GitArgs>(input)?
+		.into_iter()
+		.map(|(key, span, value)| (LitStr::new(&key, span), value.0.iter().map(|x| x.value()).collect()))
+		.collect())
+}
+
+/// A `("pattern", "label")` pair of the `rules` argument.
+pub struct Rule {
+	pub pattern: LitStr,
+	pub label: LitStr,
+}
+
+impl syn::parse::Parse for Rule {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let content;
+		syn::parenthesized!(content in input);
+		let pattern: LitStr = content.parse()?;
+		if !pattern.value().starts_with("tag:") && !pattern.value().starts_with("branch:") {
+			return Err(syn::Error::new_spanned(pattern, "expected a pattern starting with `tag:` or `branch:`"));
+		}
+		let _: Comma = content.parse()?;
+		let label = content.parse()?;
+		let _: Option<Comma> = content.parse()?;
+		Ok(Self { pattern, label })
+	}
+}
+
+/// A `NAME = kind()` item of `git_constants!()`.
+pub struct Constant {
+	pub name: Ident,
+	pub kind: Ident,
+}
+
+impl syn::parse::Parse for Constant {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let name = input.parse()?;
+		let _: syn::Token![=] = input.parse()?;
+		let kind = input.parse()?;
+		let content;
+		syn::parenthesized!(content in input);
+		if !content.is_empty() {
+			return Err(content.error("expected `()`"));
+		}
+		Ok(Self { name, kind })
+	}
+}
+
+/// The arguments of `git_is_at_tag!()`: the name of a tag, optionally followed by named arguments.
+pub struct TagArgs {
+	pub tag: LitStr,
+	pub args: Args,
+}
+
+impl syn::parse::Parse for TagArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let tag: LitStr = input.parse()?;
+		if tag.value().is_empty() {
+			return Err(syn::Error::new_spanned(tag, "the tag can not be empty"));
+		}
+		let args = if input.is_empty() {
+			Args::default()
+		} else {
+			let _: Comma = input.parse()?;
+			input.parse()?
+		};
+		Ok(Self { tag, args })
+	}
+}
+
+impl Args {
+	/// Get the repository to run git in, with the options of the `env`, `inherit_git_env` and `allow_unsafe_directory` arguments.
+	///
+	/// If the `GIT_VERSION_GITDIR_FILE` environment variable is set, the git directory is taken from the file with that name,
+	/// if there is one in the directory or its parents.
+	///
+	/// With `relative_to = "source"`, the directory of the source file invoking the macro is used instead, if it is known.
+	/// If the `path` argument is given, it is resolved relative to the directory.
+	///
+	/// The time budget of the `budget_ms` argument starts when this is called.
+	pub fn repo(&self, dir: impl Into<std::path::PathBuf>) -> Repo {
+		let dir = dir.into();
+		let dir = match self.relative_to {
+			Some(RelativeTo::Source) => source_dir().unwrap_or(dir),
+			None | Some(RelativeTo::Manifest) => dir,
+		};
+		let dir = match &self.path {
+			Some(path) => dir.join(path.value()),
+			None => dir,
+		};
+		let mut repo = Repo::new(dir)
+			.with_cache(true)
+			.with_inherit_git_env(self.inherit_git_env.unwrap_or(false))
+			.with_allow_unsafe_directory(self.allow_unsafe_directory.unwrap_or(false));
+		if let Some(name) = std::env::var_os("GIT_VERSION_GITDIR_FILE").filter(|x| !x.is_empty()) {
+			match git::find_gitdir_file(repo.dir(), name) {
+				Ok(Some((git_dir, work_tree))) => repo = repo.with_git_dir(git_dir, work_tree),
+				Ok(None) => (),
+				Err(e) => eprintln!("warning: ignoring GIT_VERSION_GITDIR_FILE: {}", e),
+			}
+		}
+		if let Some(budget_ms) = self.budget_ms {
+			repo = repo.with_deadline(std::time::Instant::now() + std::time::Duration::from_millis(budget_ms));
+		}
+		self.env.iter()
+			.flatten()
+			.fold(repo, |repo, (key, value)| repo.with_env(key, value))
+	}
+
+	/// Get the repository selected by the `repo` argument, starting from the repository containing the crate.
+	pub fn select_repo(&self, repo: &Repo) -> Result<Repo, String> {
+		match self.repo_select {
+			None | Some(RepoSelect::Current) => Ok(repo.clone()),
+			Some(RepoSelect::Superproject) => repo.superproject(),
+		}
+	}
+
+	/// Get the arguments for `git describe`, using the default arguments if none were given.
+	///
+	/// The `match`, `exclude`, `abbrev`, `dirty` and `broken` arguments are added as the flags with the same name.
+	pub fn describe_args(&self) -> Vec<String> {
+		let mut args = self.git_args.as_ref().map_or_else(
+			|| vec!["--always".to_string(), "--dirty=-modified".to_string()],
+			|list| list.iter().map(|x| x.value()).collect(),
+		);
+		args.extend(self.match_patterns.iter().flatten().map(|x| format!("--match={}", x.value())));
+		args.extend(self.exclude_patterns.iter().flatten().map(|x| format!("--exclude={}", x.value())));
+		// A later `--abbrev` overrides an earlier one, so this takes precedence over the `args`.
+		args.extend(self.abbrev_len.map(|len| format!("--abbrev={}", len)));
+		// The same goes for `--dirty` and `--broken`.
+		args.extend(self.dirty_suffix.as_ref().map(|x| format!("--dirty={}", x.value())));
+		args.extend(self.broken_suffix.as_ref().map(|x| format!("--broken={}", x.value())));
+		// The worktree only belongs to `HEAD`, so the dirty and broken suffixes do not apply to another ref.
+		if self.git_ref.is_some() {
+			args.retain(|arg| git::dirty_marker(arg).is_none() && git::broken_marker(arg).is_none());
+		}
+		args
+	}
+
+	/// Get the arguments for `git describe` for the submodule at the given path.
+	///
+	/// These are the arguments given for the submodule in the `overrides` argument,
+	/// or the same arguments as for [`Self::describe_args()`] if there are none.
+	pub fn submodule_describe_args(&self, path: &str) -> Vec<String> {
+		self.overrides.iter()
+			.flatten()
+			.find(|(key, _)| key.value() == path)
+			.map_or_else(|| self.describe_args(), |(_, args)| args.clone())
+	}
+
+	/// Get the length of abbreviated hashes given with the `--abbrev` flag for `git describe`, if any.
+	///
+	/// `--abbrev=0` is ignored, since it disables the hash.
+	pub fn abbrev(&self) -> Option<usize> {
+		self.describe_args()
+			.iter()
+			.filter_map(|arg| arg.strip_prefix("--abbrev=")?.parse().ok())
+			.next_back()
+			.filter(|&len| len > 0)
+	}
+
+	/// Get the dirty marker that `git describe` appends with the `--dirty` or `--broken` flag, if any.
+	pub fn dirty_marker(&self) -> Option<String> {
+		git::describe_markers(self.describe_args().iter().map(|x| x.as_str())).0
+	}
+
+	/// Get the broken marker that `git describe` appends with the `--broken` flag when the status can not be determined, if any.
+	pub fn broken_marker(&self) -> Option<String> {
+		git::describe_markers(self.describe_args().iter().map(|x| x.as_str())).1
+	}
+
+	/// Check if the worktree is dirty using `git status`, instead of leaving it to `git describe --dirty`.
+	///
+	/// This is needed for the `untracked` and `dirty_exclude` arguments.
+	pub fn dirty_from_status(&self) -> bool {
+		self.untracked.is_some() || self.dirty_exclude.is_some()
+	}
+
+	/// Get the changed files that make the worktree dirty, taking `untracked` and `dirty_exclude` into account.
+	pub fn dirty_status(&self, repo: &Repo) -> Result<Vec<git::StatusEntry>, String> {
+		let excludes: Vec<String> = self.dirty_exclude.iter().flatten().map(|x| x.value()).collect();
+		repo.status_excluding(self.untracked.unwrap_or(Untracked::No), &excludes)
+	}
+
+	/// Get the patch ID of the uncommitted changes, abbreviated like the commit hash, or `None` if there are none.
+	///
+	/// Files matching the `dirty_exclude` argument are not included.
+	pub fn patch_id(&self, repo: &Repo) -> Result<Option<String>, String> {
+		let excludes: Vec<String> = self.dirty_exclude.iter().flatten().map(|x| x.value()).collect();
+		let len = self.abbrev().unwrap_or(7);
+		Ok(repo.patch_id(&excludes)?.map(|id| id[..len.min(id.len())].to_owned()))
+	}
+
+	/// Get the suffix of the `target_suffix` argument for the kind of target being compiled, if any.
+	pub fn target_suffix(&self) -> Option<String> {
+		let kind = TargetKind::from_env();
+		self.target_suffix.iter()
+			.flatten()
+			.find(|(x, _)| *x == kind)
+			.map(|(_, suffix)| suffix.value())
+	}
+
+	/// Check that only the given arguments were passed to the macro.
+	pub fn check_allowed(&self, macro_name: &str, allowed: &[&str]) -> syn::Result<()> {
+		for ident in &self.names {
+			if !allowed.iter().any(|x| ident == x) {
+				return Err(syn::Error::new_spanned(ident, format!("invalid argument `{}` for `{}!()`", ident, macro_name)));
+			}
+		}
+		Ok(())
+	}
 }
 
 impl syn::parse::Parse for Args {
@@ -19,7 +501,8 @@ impl syn::parse::Parse for Args {
 			if input.is_empty() {
 				break;
 			}
-			let ident: Ident = input.parse()?;
+			// Argument names can be keywords, like `match`.
+			let ident = input.call(Ident::parse_any)?;
 			let _: syn::token::Eq = input.parse()?;
 			let check_dup = |dup: bool| {
 				if dup {
@@ -35,6 +518,27 @@ impl syn::parse::Parse for Args {
 					syn::bracketed!(content in input);
 					result.git_args = Some(Punctuated::parse_terminated(&content)?);
 				}
+				"match" => {
+					check_dup(result.match_patterns.is_some())?;
+					result.match_patterns = Some(parse_patterns(input)?);
+				}
+				"exclude" => {
+					check_dup(result.exclude_patterns.is_some())?;
+					result.exclude_patterns = Some(parse_patterns(input)?);
+				}
+				"dirty" => {
+					check_dup(result.dirty_suffix.is_some())?;
+					result.dirty_suffix = Some(input.parse()?);
+				}
+				"broken" => {
+					check_dup(result.broken_suffix.is_some())?;
+					result.broken_suffix = Some(input.parse()?);
+				}
+				"abbrev" => {
+					check_dup(result.abbrev_len.is_some())?;
+					let value: LitInt = input.parse()?;
+					result.abbrev_len = Some(value.base10_parse()?);
+				}
 				"prefix" => {
 					check_dup(result.prefix.is_some())?;
 					result.prefix = Some(input.parse()?);
@@ -55,13 +559,305 @@ impl syn::parse::Parse for Args {
 					check_dup(result.fallback.is_some())?;
 					result.fallback = Some(input.parse()?);
 				}
+				"pattern" => {
+					check_dup(result.pattern.is_some())?;
+					result.pattern = Some(input.parse()?);
+				}
+				"since" => {
+					check_dup(result.since.is_some())?;
+					result.since = Some(input.parse()?);
+				}
+				"max_len" => {
+					check_dup(result.max_len.is_some())?;
+					let value: LitInt = input.parse()?;
+					result.max_len = Some(value.base10_parse()?);
+				}
+				"overflow" => {
+					check_dup(result.overflow.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.overflow = Some(match value.value().as_str() {
+						"error" => Overflow::Error,
+						"truncate" => Overflow::Truncate,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"error\" or \"truncate\"")),
+					});
+				}
+				"combine_format" => {
+					check_dup(result.combine_format.is_some())?;
+					result.combine_format = Some(input.parse()?);
+				}
+				"validate" => {
+					check_dup(result.validate.is_some())?;
+					result.validate = Some(input.parse()?);
+				}
+				"replace" => {
+					check_dup(result.replace.is_some())?;
+					let content;
+					syn::bracketed!(content in input);
+					result.replace = Some(Punctuated::parse_terminated(&content)?);
+				}
+				"dirty_cause" => {
+					check_dup(result.dirty_cause.is_some())?;
+					if input.peek(LitInt) {
+						let value: LitInt = input.parse()?;
+						result.dirty_cause = Some(DirtyCause::Paths(value.base10_parse()?));
+					} else {
+						let value: LitStr = input.parse()?;
+						if value.value() != "counts" {
+							return Err(syn::Error::new_spanned(value, "expected \"counts\" or an integer"));
+						}
+						result.dirty_cause = Some(DirtyCause::Counts);
+					}
+				}
+				"refs" => {
+					check_dup(result.refs.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.refs = Some(match value.value().as_str() {
+						"tags" => Refs::Tags,
+						"all" => Refs::All,
+						"branches" => Refs::Branches,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"tags\", \"all\" or \"branches\"")),
+					});
+				}
+				"ref" => {
+					check_dup(result.git_ref.is_some())?;
+					let value: LitStr = input.parse()?;
+					if value.value().is_empty() {
+						return Err(syn::Error::new_spanned(value, "the ref can not be empty"));
+					}
+					result.git_ref = Some(value);
+				}
+				"untracked" => {
+					check_dup(result.untracked.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.untracked = Some(match value.value().as_str() {
+						"no" => Untracked::No,
+						"normal" => Untracked::Normal,
+						"all" => Untracked::All,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"no\", \"normal\" or \"all\"")),
+					});
+				}
+				"detached" => {
+					check_dup(result.detached.is_some())?;
+					result.detached = Some(if input.peek(LitStr) {
+						Detached::Text(input.parse()?)
+					} else {
+						let value: Ident = input.parse()?;
+						match value.to_string().as_str() {
+							"error" => Detached::Error,
+							"short_hash" => Detached::ShortHash,
+							_ => return Err(syn::Error::new_spanned(value, "expected `error`, `short_hash` or a string literal")),
+						}
+					});
+				}
+				"max_count" => {
+					check_dup(result.max_count.is_some())?;
+					let value: LitInt = input.parse()?;
+					result.max_count = Some(value.base10_parse()?);
+				}
+				"short" => {
+					check_dup(result.short.is_some())?;
+					let value: LitInt = input.parse()?;
+					let short = value.base10_parse()?;
+					if short == 0 {
+						return Err(syn::Error::new_spanned(value, "`short` must be at least 1"));
+					}
+					result.short = Some(short);
+				}
+				"uppercase" => {
+					check_dup(result.uppercase.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.uppercase = Some(value.value);
+				}
+				"fallback_env" => {
+					check_dup(result.fallback_env.is_some())?;
+					result.fallback_env = Some(input.parse()?);
+				}
+				"relative_to" => {
+					check_dup(result.relative_to.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.relative_to = Some(match value.value().as_str() {
+						"manifest" => RelativeTo::Manifest,
+						"source" => RelativeTo::Source,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"manifest\" or \"source\"")),
+					});
+				}
+				"path" => {
+					check_dup(result.path.is_some())?;
+					result.path = Some(input.parse()?);
+				}
+				"fallback_file" => {
+					check_dup(result.fallback_file.is_some())?;
+					result.fallback_file = Some(input.parse()?);
+				}
+				"sources" => {
+					check_dup(result.sources.is_some())?;
+					let content;
+					syn::bracketed!(content in input);
+					result.sources = Some(Punctuated::parse_terminated(&content)?);
+				}
+				"env_override" => {
+					check_dup(result.env_override.is_some())?;
+					result.env_override = Some(input.parse()?);
+				}
+				"packaged_format" => {
+					check_dup(result.packaged_format.is_some())?;
+					result.packaged_format = Some(input.parse()?);
+				}
+				"post" => {
+					check_dup(result.post.is_some())?;
+					result.post = Some(input.parse()?);
+				}
+				"repo" => {
+					check_dup(result.repo_select.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.repo_select = Some(match value.value().as_str() {
+						"current" => RepoSelect::Current,
+						"superproject" => RepoSelect::Superproject,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"current\" or \"superproject\"")),
+					});
+				}
+				"format" => {
+					check_dup(result.format.is_some())?;
+					result.format = Some(input.parse()?);
+				}
+				"semver" => {
+					check_dup(result.semver.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.semver = Some(value.value);
+				}
+				"tz" => {
+					check_dup(result.tz.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.tz = Some(match value.value().as_str() {
+						"utc" => TimeZone::Utc,
+						"local" => TimeZone::Local,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"utc\" or \"local\"")),
+					});
+				}
+				"rules" => {
+					check_dup(result.rules.is_some())?;
+					let content;
+					syn::bracketed!(content in input);
+					result.rules = Some(Punctuated::parse_terminated(&content)?);
+				}
+				"key" => {
+					check_dup(result.key.is_some())?;
+					result.key = Some(input.parse()?);
+				}
+				"remote" => {
+					check_dup(result.remote.is_some())?;
+					result.remote = Some(input.parse()?);
+				}
+				"budget_ms" => {
+					check_dup(result.budget_ms.is_some())?;
+					let value: LitInt = input.parse()?;
+					result.budget_ms = Some(value.base10_parse()?);
+				}
+				"static_ref" => {
+					check_dup(result.static_ref.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.static_ref = Some(value.value);
+				}
+				"as_slice" => {
+					check_dup(result.as_slice.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.as_slice = Some(value.value);
+				}
+				"dirty_exclude" => {
+					check_dup(result.dirty_exclude.is_some())?;
+					let content;
+					syn::bracketed!(content in input);
+					result.dirty_exclude = Some(Punctuated::parse_terminated(&content)?);
+				}
+				"paths" => {
+					check_dup(result.paths.is_some())?;
+					let content;
+					syn::bracketed!(content in input);
+					result.paths = Some(Punctuated::parse_terminated(&content)?);
+				}
+				"urls" => {
+					check_dup(result.urls.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.urls = Some(value.value);
+				}
+				"tag" => {
+					check_dup(result.tag.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.tag = Some(value.value);
+				}
+				"descendant" => {
+					check_dup(result.descendant.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.descendant = Some(value.value);
+				}
+				"ignore_git_errors" => {
+					check_dup(result.ignore_git_errors.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.ignore_git_errors = Some(value.value);
+				}
+				"out_of_sync" => {
+					check_dup(result.out_of_sync.is_some())?;
+					result.out_of_sync = Some(input.parse()?);
+				}
+				"overrides" => {
+					check_dup(result.overrides.is_some())?;
+					result.overrides = Some(parse_overrides(input)?);
+				}
+				"require_clean" => {
+					check_dup(result.require_clean.is_some())?;
+					if input.peek(LitStr) {
+						let value: LitStr = input.parse()?;
+						if value.value() != "release" {
+							return Err(syn::Error::new_spanned(value, "expected a boolean or \"release\""));
+						}
+						result.require_clean = Some(RequireClean::Release);
+					} else {
+						let value: syn::LitBool = input.parse()?;
+						result.require_clean = value.value.then_some(RequireClean::Always);
+					}
+				}
+				"allow_unsafe_directory" => {
+					check_dup(result.allow_unsafe_directory.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.allow_unsafe_directory = Some(value.value);
+				}
+				"track" => {
+					check_dup(result.track.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.track = Some(value.value);
+				}
+				"inherit_git_env" => {
+					check_dup(result.inherit_git_env.is_some())?;
+					let value: syn::LitBool = input.parse()?;
+					result.inherit_git_env = Some(value.value);
+				}
+				"check" => {
+					check_dup(result.check.is_some())?;
+					let value: LitStr = input.parse()?;
+					result.check = Some(match value.value().as_str() {
+						"warn" => Check::Warn,
+						"error" => Check::Error,
+						_ => return Err(syn::Error::new_spanned(value, "expected \"warn\" or \"error\"")),
+					});
+				}
+				"target_suffix" => {
+					check_dup(result.target_suffix.is_some())?;
+					result.target_suffix = Some(parse_target_suffix(input)?);
+				}
+				"env" => {
+					check_dup(result.env.is_some())?;
+					result.env = Some(parse_env(input)?);
+				}
 				x => Err(error!("Unexpected argument name `{}`", x))?,
 			}
+			result.names.push(ident);
 			if input.is_empty() {
 				break;
 			}
 			let _: Comma = input.parse()?;
 		}
+		// Dependencies are added in many places, so the `track` argument applies to the whole expansion.
+		crate::utils::set_tracking(result.track.unwrap_or(true));
 		Ok(result)
 	}
 }
@@ -0,0 +1,218 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{Expr, Lit};
+
+use git_version_core::template::Segment;
+
+/// A version string produced by `git_version!()`, before it is turned into tokens.
+pub enum VersionOutput {
+	/// A version made of string pieces that are concatenated at compile time.
+	Concat {
+		/// Tokens that make the compiler depend on the git state, if any.
+		dependencies: Option<TokenStream2>,
+		/// The pieces of the version string.
+		pieces: Vec<Expr>,
+	},
+
+	/// A user provided fallback expression, used as-is.
+	Fallback(Expr),
+}
+
+/// Where the version produced by `git_version!()` came from.
+#[derive(Copy, Clone)]
+pub enum Source {
+	/// The version was produced by git.
+	Git,
+
+	/// The version was taken from the environment variable given by `env_override`.
+	Env,
+
+	/// The version was made from the `.cargo_vcs_info.json` file, possibly using `packaged_format`.
+	Packaged,
+
+	/// The version was read from the `.git_archival.txt` file of an archive made by `git archive`.
+	Archive,
+
+	/// The version was read from the file given by `fallback_file`.
+	File,
+
+	/// Cargo's version was used with `cargo_prefix` or `cargo_suffix`.
+	Cargo,
+
+	/// The `fallback` argument was used.
+	Fallback,
+}
+
+impl Source {
+	/// Get the tag used for the source by `git_version_source!()` and `VersionSource::parse()`.
+	pub fn tag(self) -> &'static str {
+		match self {
+			Self::Git => "git",
+			Self::Env => "env",
+			Self::Packaged => "packaged",
+			Self::Archive => "archive",
+			Self::File => "file",
+			Self::Cargo => "cargo",
+			Self::Fallback => "fallback",
+		}
+	}
+}
+
+impl VersionOutput {
+	/// Create a version from a version string with an optional prefix and suffix.
+	pub fn concat(dependencies: Option<TokenStream2>, prefix: Option<Expr>, version: String, suffix: Option<Expr>) -> Self {
+		let mut pieces = Vec::new();
+		pieces.extend(prefix);
+		pieces.push(syn::parse_quote!(#version));
+		pieces.extend(suffix);
+		Self::Concat { dependencies, pieces }
+	}
+
+	/// Create a version from a format string combining the git version with Cargo's version.
+	///
+	/// The `{git}` placeholder is replaced by the git version with its prefix and suffix,
+	/// the `{cargo}` placeholder is replaced by `cargo_version`, and the `{date}` placeholder by `date`.
+	pub fn combine(
+		dependencies: Option<TokenStream2>,
+		format: &[Segment],
+		prefix: Option<Expr>,
+		git_version: String,
+		suffix: Option<Expr>,
+		cargo_version: String,
+		date: String,
+	) -> Self {
+		let mut pieces = Vec::new();
+		for segment in format {
+			match segment {
+				Segment::Text(text) => pieces.push(syn::parse_quote!(#text)),
+				Segment::Placeholder(name) if name == "git" => {
+					pieces.extend(prefix.clone());
+					pieces.push(syn::parse_quote!(#git_version));
+					pieces.extend(suffix.clone());
+				}
+				Segment::Placeholder(name) if name == "date" => pieces.push(syn::parse_quote!(#date)),
+				Segment::Placeholder(_) => pieces.push(syn::parse_quote!(#cargo_version)),
+				// Not produced by `template::parse()`, which is used for `combine_format`.
+				Segment::Conditional(..) => (),
+			}
+		}
+		Self::Concat { dependencies, pieces }
+	}
+
+	/// Add tokens that make the compiler depend on something, such as an environment variable.
+	///
+	/// User provided fallbacks are used as-is, so nothing is added to them.
+	pub fn add_dependencies(&mut self, tokens: TokenStream2) {
+		if let Self::Concat { dependencies, .. } = self {
+			*dependencies = Some(match dependencies.take() {
+				Some(dependencies) => quote!(#dependencies #tokens),
+				None => tokens,
+			});
+		}
+	}
+
+	/// Get the value of the version string.
+	///
+	/// This requires all pieces to be string literals.
+	/// The `context` is used in the error message, like "when using `max_len`".
+	pub fn value(&self, context: &str) -> syn::Result<String> {
+		match self {
+			Self::Concat { pieces, .. } => {
+				let mut value = String::new();
+				for piece in pieces {
+					value.push_str(&literal_str(piece, context)?);
+				}
+				Ok(value)
+			}
+			Self::Fallback(fallback) => literal_str(fallback, context),
+		}
+	}
+
+	/// Get the value of the version string for the expansion report.
+	///
+	/// If not all pieces are string literals, the tokens of the expression are used instead.
+	pub fn report_value(&self) -> String {
+		self.value("").unwrap_or_else(|_| self.to_str_tokens().to_string())
+	}
+
+	/// Replace the version string by a single string literal.
+	///
+	/// A byte string fallback, as used by `git_version_bytes!()`, is replaced by a byte string literal.
+	pub fn set_value(&mut self, value: String) {
+		match self {
+			Self::Concat { pieces, .. } => *pieces = vec![syn::parse_quote!(#value)],
+			Self::Fallback(Expr::Lit(syn::ExprLit { lit: Lit::ByteStr(lit), .. })) => {
+				*lit = syn::LitByteStr::new(value.as_bytes(), lit.span());
+			}
+			Self::Fallback(fallback) => *fallback = syn::parse_quote!(#value),
+		}
+	}
+
+	/// Get the tokens for a `&'static str` expression.
+	///
+	/// Without dependencies, the expansion is a string literal.
+	/// Otherwise, the value is assigned to a constant inside a block,
+	/// which makes the compiler check that it is a `&'static str` that can be evaluated at compile time.
+	/// Fallbacks that are not a string literal are checked the same way.
+	pub fn to_str_tokens(&self) -> TokenStream2 {
+		match self {
+			Self::Concat { dependencies: Some(dependencies), pieces } => quote!({
+				#dependencies;
+				const VALUE: &'static str = concat!(#(#pieces),*);
+				VALUE
+			}),
+			Self::Concat { dependencies: None, pieces } => quote!(concat!(#(#pieces),*)),
+			Self::Fallback(fallback @ Expr::Lit(syn::ExprLit { lit: Lit::Str(_), .. })) => fallback.to_token_stream(),
+			Self::Fallback(fallback) => quote!({
+				const VALUE: &'static str = #fallback;
+				VALUE
+			}),
+		}
+	}
+
+	/// Get the tokens for a byte string literal.
+	///
+	/// This requires all pieces to be string literals, since they can not be concatenated otherwise.
+	pub fn to_bytes_tokens(&self, macro_name: &str) -> syn::Result<TokenStream2> {
+		match self {
+			Self::Concat { dependencies, pieces } => {
+				let mut value = Vec::new();
+				for piece in pieces {
+					value.extend(literal_bytes(piece, &format!("for `{}!()`", macro_name))?);
+				}
+				let value = syn::LitByteStr::new(&value, proc_macro2::Span::call_site());
+				match dependencies {
+					Some(dependencies) => Ok(quote!({
+						#dependencies;
+						#value
+					})),
+					None => Ok(value.to_token_stream()),
+				}
+			}
+			Self::Fallback(fallback) => {
+				let value = literal_bytes(fallback, &format!("for `{}!()`", macro_name))?;
+				let value = syn::LitByteStr::new(&value, proc_macro2::Span::call_site());
+				Ok(value.to_token_stream())
+			}
+		}
+	}
+}
+
+/// Get the value of a string literal.
+fn literal_str(expr: &Expr, context: &str) -> syn::Result<String> {
+	match expr {
+		Expr::Lit(syn::ExprLit { lit: Lit::Str(lit), .. }) => Ok(lit.value()),
+		// Byte string fallbacks of `git_version_bytes!()`, which can be processed like strings if they are valid UTF-8.
+		Expr::Lit(syn::ExprLit { lit: Lit::ByteStr(lit), .. }) => String::from_utf8(lit.value())
+			.map_err(|_| syn::Error::new_spanned(lit, format!("expected a UTF-8 byte string literal {}", context))),
+		expr => Err(syn::Error::new_spanned(expr, format!("expected a string literal {}", context))),
+	}
+}
+
+/// Get the value of a string or byte string literal.
+fn literal_bytes(expr: &Expr, context: &str) -> syn::Result<Vec<u8>> {
+	match expr {
+		Expr::Lit(syn::ExprLit { lit: Lit::ByteStr(lit), .. }) => Ok(lit.value()),
+		expr => Ok(literal_str(expr, context)?.into_bytes()),
+	}
+}
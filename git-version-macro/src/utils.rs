@@ -1,151 +1,404 @@
-use std::ffi::OsStr;
+use std::cell::Cell;
 use std::path::{PathBuf, Path};
-use std::process::Command;
-
-/// Run `git describe` for the current working directory with custom flags to get version information from git.
-pub fn describe<I, S>(dir: impl AsRef<Path>, args: I) -> Result<String, String>
-where
-	I: IntoIterator<Item = S>,
-	S: AsRef<OsStr>,
-{
-	let dir = dir.as_ref();
-	run_git("git describe", Command::new("git")
-		.arg("-C")
-		.arg(dir)
-		.arg("describe").args(args))
-}
-
-/// Get the git directory for the given directory.
-pub fn git_dir(dir: impl AsRef<Path>) -> Result<PathBuf, String> {
-	let dir = dir.as_ref();
-	let path = run_git("git rev-parse", Command::new("git")
-		.arg("-C")
-		.arg(dir)
-		.args(["rev-parse", "--git-dir"]))?;
-	Ok(dir.join(path))
-}
-
-/// Run `git submodule foreach` command to discover submodules in the project.
-pub fn get_submodules(dir: impl AsRef<Path>) -> Result<Vec<String>, String> {
-	let dir = dir.as_ref();
-	let result = run_git("git submodule",
-		Command::new("git")
-			.arg("-C")
-			.arg(dir)
-			.arg("submodule")
-			.arg("foreach")
-			.arg("--quiet")
-			.arg("--recursive")
-			.arg("echo $displaypath"),
-	)?;
-
-	Ok(result.lines()
-		.filter(|x| !x.is_empty())
-		.map(|x| x.to_owned())
-		.collect()
-	)
-}
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use git_version_core::{git, report};
+use proc_macro2::{Delimiter, TokenStream as TokenStream2, TokenTree};
 
+/// Canonicalize a path and convert it to a string, so it can be used in a string literal.
+///
+/// Paths are quoted in error messages, so that spaces in them remain visible.
 pub fn canonicalize_path(path: &Path) -> syn::Result<String> {
 	path.canonicalize()
-		.map_err(|e| error!("failed to canonicalize {}: {}", path.display(), e))?
+		.map_err(|e| error!("failed to canonicalize {:?}: {}", path, e))?
 		.into_os_string()
 		.into_string()
-		.map_err(|file| error!("invalid UTF-8 in path to {}", PathBuf::from(file).display()))
+		.map_err(|file| error!("invalid UTF-8 in path {:?}", PathBuf::from(file)))
 }
 
-/// Create a token stream representing dependencies on the git state.
-pub fn git_dependencies() -> syn::Result<proc_macro2::TokenStream> {
-	let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
-		.ok_or_else(|| error!("CARGO_MANIFEST_DIR is not set"))?;
-	let git_dir = git_dir(manifest_dir).map_err(|e| error!("failed to determine .git directory: {}", e))?;
+/// Create a token stream representing dependencies on the git state of a repository.
+///
+/// The dependencies are the [`git::state_files()`] that exist,
+/// so commits and branch changes trigger a rebuild even without a reflog.
+/// There are no dependencies if tracking is disabled with the `track` argument.
+pub fn git_dependencies(repo: &git::Repo) -> syn::Result<proc_macro2::TokenStream> {
+	if !TRACKING.get() {
+		return Ok(TokenStream2::new());
+	}
+	let git_dir = repo.git_dir().map_err(|e| error!("failed to determine .git directory: {}", e))?;
 
-	let deps: Vec<_> = ["logs/HEAD", "index"]
+	Ok(git::state_files(&git_dir)
 		.iter()
-		.flat_map(|&file| {
-			canonicalize_path(&git_dir.join(file))
+		.filter(|file| file.exists())
+		.flat_map(|file| {
+			track_path(file)
 			.map_err(|e| eprintln!("Failed to add dependency on the git state: {}. Git state changes might not trigger a rebuild.", e))
 			.ok()
 		})
-		.collect();
-
-	Ok(quote::quote! {
-		#( include_bytes!(#deps); )*
-	})
-}
-
-fn run_git(program: &str, command: &mut std::process::Command) -> Result<String, String> {
-	let output = command
-		.stdout(std::process::Stdio::piped())
-		.stderr(std::process::Stdio::piped())
-		.spawn()
-		.map_err(|e| {
-			if e.kind() == std::io::ErrorKind::NotFound {
-				format!("Command `{}` not found: is git installed?", command.get_program().to_string_lossy())
-			} else {
-				format!("Failed to run `{}`: {}", command.get_program().to_string_lossy(), e)
+		.collect())
+}
+
+/// Make the compiler track a file, so Cargo rebuilds the crate when it changes.
+///
+/// Nothing is tracked if tracking is disabled with the `track` argument.
+///
+/// On stable Rust, this expands to an `include_bytes!()` statement for the file.
+/// With the `nightly` feature, the file is tracked with `proc_macro::tracked::path()` instead, and no tokens are needed.
+pub fn track_path(path: &Path) -> syn::Result<TokenStream2> {
+	if !TRACKING.get() {
+		return Ok(TokenStream2::new());
+	}
+	let path = canonicalize_path(path)?;
+	#[cfg(feature = "nightly")]
+	{
+		proc_macro::tracked::path(path);
+		Ok(TokenStream2::new())
+	}
+	#[cfg(not(feature = "nightly"))]
+	Ok(quote::quote!(include_bytes!(#path);))
+}
+
+/// Make the compiler track an environment variable, so Cargo rebuilds the crate when it changes.
+///
+/// Nothing is tracked if tracking is disabled with the `track` argument.
+///
+/// On stable Rust, this expands to an `option_env!()` statement for the variable.
+/// With the `nightly` feature, the variable is tracked with `proc_macro::tracked::env_var()` instead, and no tokens are needed.
+pub fn track_env(name: &str) -> TokenStream2 {
+	if !TRACKING.get() {
+		return TokenStream2::new();
+	}
+	#[cfg(feature = "nightly")]
+	{
+		let _ = proc_macro::tracked::env_var(name);
+		TokenStream2::new()
+	}
+	#[cfg(not(feature = "nightly"))]
+	quote::quote!(option_env!(#name);)
+}
+
+thread_local! {
+	/// Set when a fallback is used by the expansion that is currently running on this thread.
+	static FALLBACK_USED: Cell<bool> = const { Cell::new(false) };
+
+	/// Cleared when the expansion that is currently running on this thread should not track any dependencies.
+	static TRACKING: Cell<bool> = const { Cell::new(true) };
+}
+
+/// Choose whether the expansion that is currently running on this thread tracks its dependencies.
+pub fn set_tracking(enabled: bool) {
+	TRACKING.set(enabled);
+}
+
+/// Add a hint about the macro arguments that can solve an error from git, if there is one.
+///
+/// The errors of `git_version_core` do not mention macro arguments, since it is also used without the macros.
+pub fn with_hint(message: String) -> String {
+	if message.contains("git may be blocked by a security policy") {
+		format!(
+			"{}. Use the `env` argument to set a `PATH` with a git that may run, or the `fallback` argument to build without git.",
+			message,
+		)
+	} else if message.contains("the repository is owned by a different user") {
+		format!("{} Or use the `allow_unsafe_directory` argument.", message)
+	} else {
+		message
+	}
+}
+
+/// Record that a fallback is used instead of the output of git.
+///
+/// This prints a warning if the fallback is used because git did not finish within the time budget.
+pub fn used_fallback(repo: &git::Repo, error: &str) {
+	FALLBACK_USED.set(true);
+	if repo.deadline_passed() {
+		eprintln!("warning: using fallback because git exceeded the time budget: {}", error);
+	}
+}
+
+/// A macro expansion that is recorded in the expansion report, if reporting is enabled.
+pub struct Report {
+	path: Option<PathBuf>,
+	macro_name: &'static str,
+	inputs: String,
+	start: Instant,
+}
+
+impl Report {
+	/// Start recording the expansion of a macro.
+	pub fn start(macro_name: &'static str, input: &proc_macro::TokenStream) -> Self {
+		let path = report::path();
+		let inputs = if path.is_some() { input.to_string() } else { String::new() };
+		FALLBACK_USED.set(false);
+		TRACKING.set(true);
+		Self { path, macro_name, inputs, start: Instant::now() }
+	}
+
+	/// Finish recording the expansion, and append it to the report.
+	///
+	/// Failing to write the report only prints a warning, so reporting never breaks the build.
+	pub fn finish(self, output: Result<String, &syn::Error>) {
+		let Some(path) = self.path else {
+			return;
+		};
+		let crate_name = std::env::var("CARGO_CRATE_NAME")
+			.or_else(|_| std::env::var("CARGO_PKG_NAME"))
+			.unwrap_or_default();
+		let error = output.as_ref().err().map(|e| e.to_string());
+		let record = report::Record {
+			crate_name: &crate_name,
+			macro_name: self.macro_name,
+			inputs: &self.inputs,
+			output: match (&output, &error) {
+				(Ok(output), _) => Ok(output),
+				(Err(_), error) => Err(error.as_deref().unwrap_or_default()),
+			},
+			fallback_used: FALLBACK_USED.get(),
+			duration: self.start.elapsed(),
+		};
+		if let Err(e) = report::append(&path, &record) {
+			eprintln!("warning: failed to write git-version expansion report: {}", e);
+		}
+	}
+}
+
+/// Get the value that the expansion of a macro embeds, for the expansion report.
+///
+/// Statements that only track dependencies are left out, and the constants and statics that hold the value are replaced by it.
+/// A string literal is given as its value, like [`VersionOutput::report_value()`](crate::output::VersionOutput::report_value),
+/// and other values as their tokens.
+/// For expansions that declare items, such as `git_constants!()`, the value of each named constant is given as `NAME = value`.
+pub fn report_value(tokens: &TokenStream2) -> String {
+	// Look inside a block expression.
+	let mut tokens = tokens.clone();
+	while let [TokenTree::Group(group)] = tokens.clone().into_iter().collect::<Vec<_>>().as_slice() {
+		if group.delimiter() != Delimiter::Brace {
+			break;
+		}
+		tokens = group.stream();
+	}
+
+	let mut bindings = Vec::new();
+	let mut value = None;
+	for statement in split_statements(tokens) {
+		let is_dependency = matches!(statement.first(), Some(TokenTree::Ident(x)) if x == "include_bytes" || x == "option_env");
+		if is_dependency {
+			continue;
+		}
+		match binding(&statement) {
+			Some((name, _)) if name == "_" => (),
+			Some(binding) => bindings.push(binding),
+			None => value = Some(statement.into_iter().collect::<TokenStream2>()),
+		}
+	}
+
+	let resolve = |mut value: TokenStream2| {
+		// Replace a (reference to a) constant by its value, which can again be a constant.
+		for _ in 0..bindings.len() {
+			let trees: Vec<_> = value.clone().into_iter().collect();
+			let name = match trees.as_slice() {
+				[TokenTree::Ident(name)] | [TokenTree::Punct(_), TokenTree::Ident(name)] => name.to_string(),
+				_ => break,
+			};
+			match bindings.iter().find(|(x, _)| *x == name) {
+				Some((_, x)) => value = x.clone(),
+				None => break,
 			}
-		})?
-		.wait_with_output()
-		.map_err(|e| format!("Failed to wait for `{}`: {}", program, e))?;
-
-	let output = collect_output(program, output)?;
-	let output = strip_trailing_newline(output);
-	let output =
-		String::from_utf8(output).map_err(|_| format!("Failed to parse output of `{}`: output contains invalid UTF-8", program))?;
-	Ok(output)
-}
-
-/// Check if a command ran successfully, and if not, return a verbose error.
-fn collect_output(program: &str, output: std::process::Output) -> Result<Vec<u8>, String> {
-	// If the command succeeded, just return the output as is.
-	if output.status.success() {
-		return Ok(output.stdout);
-
-	// If the command terminated with non-zero exit code, return an error.
-	} else if let Some(status) = output.status.code() {
-		// Include the first line of stderr in the error message, if it's valid UTF-8 and not empty.
-		let message = output
-			.stderr
-			.split(|c| *c == b'\n')
-			.next()
-			.and_then(|x| std::str::from_utf8(x).ok())
-			.filter(|x| !x.is_empty());
-		if let Some(message) = message {
-			return Err(format!("{} exited with status {}: {}", program, status, message));
-		} else {
-			return Err(format!("{} exited with status {}", program, status));
 		}
+		// The value can be a block of its own, such as an array after the dependencies.
+		match value.clone().into_iter().collect::<Vec<_>>().as_slice() {
+			[TokenTree::Group(group)] if group.delimiter() == Delimiter::Brace => report_value(&value),
+			_ => literal_value(value),
+		}
+	};
+	match value {
+		Some(value) => resolve(value),
+		None if bindings.is_empty() => "()".to_owned(),
+		None => bindings.iter()
+			.map(|(name, value)| format!("{} = {}", name, literal_value(value.clone())))
+			.collect::<Vec<_>>()
+			.join(", "),
 	}
+}
 
-	// The command was killed by a signal.
-	#[cfg(unix)]
-	{
-		use std::os::unix::process::ExitStatusExt;
-		if let Some(signal) = output.status.signal() {
-			// Include the signal number on Unix.
-			return Err(format!("{} killed by signal {}", program, signal));
+/// Split tokens into statements at the semicolons that are not inside a group.
+fn split_statements(tokens: TokenStream2) -> Vec<Vec<TokenTree>> {
+	let mut statements = vec![Vec::new()];
+	for tree in tokens {
+		match &tree {
+			TokenTree::Punct(x) if x.as_char() == ';' => statements.push(Vec::new()),
+			_ => statements.last_mut().unwrap().push(tree),
 		}
 	}
+	statements.retain(|x| !x.is_empty());
+	statements
+}
+
+/// Get the name and value of a `const`, `static` or `let` statement.
+fn binding(statement: &[TokenTree]) -> Option<(String, TokenStream2)> {
+	let mut trees = statement.iter().skip_while(|x| matches!(x, TokenTree::Ident(x) if x == "pub"));
+	trees.next().filter(|x| matches!(x, TokenTree::Ident(x) if x == "const" || x == "static" || x == "let"))?;
+	let name = trees.next()?.to_string();
+	trees.find(|x| matches!(x, TokenTree::Punct(x) if x.as_char() == '='))?;
+	Some((name, trees.cloned().collect()))
+}
+
+/// Get the value of a string literal or a `concat!()` of string literals, or the tokens of any other value.
+fn literal_value(tokens: TokenStream2) -> String {
+	use syn::parse::Parser;
 
-	Err(format!("{} exitted with error", program))
+	if let Ok(lit) = syn::parse2::<syn::LitStr>(tokens.clone()) {
+		return lit.value();
+	}
+	let trees: Vec<_> = tokens.clone().into_iter().collect();
+	if let [TokenTree::Ident(name), TokenTree::Punct(_), TokenTree::Group(args)] = trees.as_slice() {
+		let parser = syn::punctuated::Punctuated::<syn::LitStr, syn::Token![,]>::parse_terminated;
+		if let (true, Ok(pieces)) = (name == "concat", parser.parse2(args.stream())) {
+			return pieces.iter().map(|x| x.value()).collect();
+		}
+	}
+	tokens.to_string()
+}
+
+/// Wrap the expansion of a macro in a call to the function given by the `post` argument, if any.
+///
+/// The result is assigned to a constant of type `ty`,
+/// so the compiler checks that the function is a `const fn` with the right signature.
+pub fn post_process(tokens: TokenStream2, post: Option<&syn::Path>, ty: TokenStream2) -> TokenStream2 {
+	match post {
+		None => tokens,
+		Some(post) => quote::quote!({
+			const VALUE: #ty = #post(#tokens);
+			VALUE
+		}),
+	}
+}
+
+/// Create the expansion of a macro that returns an array.
+///
+/// If `static_ref` is true, the array is stored in a hidden `static` and a reference to it is returned,
+/// so the array is not copied into every place the expansion is used.
+///
+/// If `as_slice` is true, a `&'static [T]` slice is returned instead of an array or a reference to an array.
+pub fn array_tokens(element_type: TokenStream2, elements: &[TokenStream2], static_ref: bool, as_slice: bool) -> TokenStream2 {
+	let len = elements.len();
+	if as_slice && static_ref {
+		quote::quote!({
+			static ARRAY: [#element_type; #len] = [#(#elements),*];
+			let slice: &'static [#element_type] = &ARRAY;
+			slice
+		})
+	} else if as_slice {
+		quote::quote!({
+			const SLICE: &[#element_type] = &[#(#elements),*];
+			SLICE
+		})
+	} else if static_ref {
+		quote::quote!({
+			static ARRAY: [#element_type; #len] = [#(#elements),*];
+			&ARRAY
+		})
+	} else if elements.is_empty() {
+		// Ensure that the type of the empty array is still known to the compiler.
+		quote::quote!({
+			let array: [#element_type; 0] = [];
+			array
+		})
+	} else {
+		quote::quote!({
+			[#(#elements),*]
+		})
+	}
 }
 
-/// Remove a trailing newline from a byte string.
-fn strip_trailing_newline(mut input: Vec<u8>) -> Vec<u8> {
-	if input.last().copied() == Some(b'\n') {
-		input.pop();
+/// Apply a function to all items on a pool of threads, and return the results in the order of the items.
+///
+/// The number of threads is limited by the available parallelism.
+/// If a call panics, the panic is resumed on the calling thread.
+pub fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+	let threads = std::thread::available_parallelism().map_or(1, |x| x.get()).min(items.len());
+	if threads <= 1 {
+		return items.iter().map(f).collect();
 	}
-	input
+
+	let next = AtomicUsize::new(0);
+	let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+		let workers: Vec<_> = (0..threads)
+			.map(|_| scope.spawn(|| {
+				let mut results = Vec::new();
+				loop {
+					let index = next.fetch_add(1, Ordering::Relaxed);
+					let Some(item) = items.get(index) else {
+						break results;
+					};
+					results.push((index, f(item)));
+				}
+			}))
+			.collect();
+		workers.into_iter()
+			.flat_map(|worker| worker.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+			.collect()
+	});
+	results.sort_by_key(|(index, _)| *index);
+	results.into_iter().map(|(_, result)| result).collect()
 }
 
 #[test]
-fn test_git_dir() {
+fn test_canonicalize_path() {
 	use assert2::{assert, let_assert};
-	use std::path::Path;
 
-	let_assert!(Ok(git_dir) = git_dir("."));
-	let_assert!(Ok(git_dir) = git_dir.canonicalize());
-	let_assert!(Ok(expected) = Path::new(env!("CARGO_MANIFEST_DIR")).join("../.git").canonicalize());
-	assert!(git_dir == expected);
+	let_assert!(Ok(tempdir) = tempfile::Builder::new().prefix("git version ünïcødé ").tempdir());
+	let file = tempdir.path().join("fïle name.txt");
+	let_assert!(Ok(()) = std::fs::write(&file, "hello"));
+	let_assert!(Ok(path) = canonicalize_path(&file));
+	assert!(path.ends_with("fïle name.txt"));
+	assert!(path.contains("git version ünïcødé "));
+
+	let_assert!(Err(e) = canonicalize_path(&tempdir.path().join("missing fïle")));
+	assert!(e.to_string().contains("missing fïle\""));
+}
+
+#[test]
+fn test_with_hint() {
+	use assert2::assert;
+
+	let message = with_hint("Permission denied running `git`: git may be blocked by a security policy on this machine".into());
+	assert!(message.contains("machine. Use the `env` argument"));
+	assert!(message.ends_with("or the `fallback` argument to build without git."));
+	let message = with_hint("git describe exited with status 128: fatal: detected dubious ownership in repository at '/repo': \
+		the repository is owned by a different user. Add it to `safe.directory` in the git configuration to use it anyway.".into());
+	assert!(message.ends_with("to use it anyway. Or use the `allow_unsafe_directory` argument."));
+	assert!(with_hint("git describe exited with status 128".into()) == "git describe exited with status 128");
+}
+
+#[test]
+fn test_report_value() {
+	use assert2::assert;
+	use quote::quote;
+
+	assert!(report_value(&quote!("v1.0")) == "v1.0");
+	assert!(report_value(&quote!({ include_bytes!("/repo/.git/HEAD"); const VALUE: &'static str = "abc"; VALUE })) == "abc");
+	assert!(report_value(&quote!({ option_env!("X"); const VALUE: &'static str = concat!("v", "1.0"); VALUE })) == "v1.0");
+	assert!(report_value(&quote!({ include_bytes!("/repo/.git/HEAD"); ; 42u64 })) == "42u64");
+	assert!(report_value(&quote!({ static ARRAY: [&str; 1] = ["a"]; let slice: &'static [&str] = &ARRAY; slice })) == "[\"a\"]");
+	assert!(report_value(&quote!({ const VALUE: &'static str = FALLBACK; VALUE })) == "FALLBACK");
+	assert!(report_value(&quote!({ include_bytes!("/a"); { let array: [&'static str; 0] = []; array } })) == "[]");
+	let items = quote!(pub const A: &str = "x"; pub const B: bool = true; const _: () = { include_bytes!("/a"); };);
+	assert!(report_value(&items) == "A = x, B = true");
+	assert!(report_value(&quote!(const _: () = { include_bytes!("/a"); };)) == "()");
+}
+
+#[test]
+fn test_parallel_map() {
+	use assert2::assert;
+
+	let items: Vec<u64> = (0..100).collect();
+	let results = parallel_map(&items, |x| {
+		// Make later items finish first, to check that the order is preserved.
+		std::thread::sleep(std::time::Duration::from_micros(100 - x));
+		x * 2
+	});
+	assert!(results == items.iter().map(|x| x * 2).collect::<Vec<_>>());
+	assert!(parallel_map(&[] as &[u64], |x| *x) == []);
 }
@@ -0,0 +1,259 @@
+//! Fixture git repositories for testing code that uses [`git-version`](https://docs.rs/git-version).
+//!
+//! Each [`Fixture`] is a directory in a new temporary directory, which is removed when the fixture is dropped.
+//! Fixtures can be put in the states that fallback and CI logic has to deal with:
+//! not a repository at all, no tags, dirty worktrees, shallow clones, submodules, or no `git` in `PATH`.
+//!
+//! Use [`Fixture::repo()`] to run the git queries that the macros of `git-version` use in a fixture:
+//!
+//! ```
+//! use git_version_testkit::Fixture;
+//!
+//! let fixture = Fixture::init()?;
+//! fixture.commit("Initial commit")?;
+//! fixture.tag("v1.0")?;
+//! fixture.make_dirty()?;
+//! assert_eq!(fixture.repo().describe(["--always", "--dirty=-modified"])?, "v1.0-modified");
+//! # Ok::<(), String>(())
+//! ```
+//!
+//! To test the macros themselves, including their arguments and fallbacks,
+//! write a crate that uses them with [`Fixture::write_crate()`] and run it with [`Fixture::cargo_run()`]:
+//!
+//! ```no_run
+//! use git_version_testkit::Fixture;
+//!
+//! let fixture = Fixture::init()?;
+//! fixture.write_crate(r#""0.3""#, r#"fn main() { print!("{}", git_version::git_version!(dirty = "-dev")); }"#)?;
+//! fixture.commit("Initial commit")?;
+//! fixture.tag("v1.0")?;
+//! fixture.make_dirty()?;
+//! assert_eq!(fixture.cargo_run("target/fixtures")?, "v1.0-dev");
+//! # Ok::<(), String>(())
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use git_version_core::git::Repo;
+
+/// A directory in a temporary directory, optionally containing a git repository.
+pub struct Fixture {
+	/// The temporary directory, which is removed on drop.
+	tempdir: tempfile::TempDir,
+
+	/// The directory of the fixture inside the temporary directory.
+	dir: PathBuf,
+}
+
+/// The configuration used for all git commands that create fixtures.
+///
+/// The global and system configuration files are not read by git in fixtures,
+/// so fixtures do not depend on the configuration of the user.
+const GIT_CONFIG: &[&str] = &[
+	"user.name=git-version-testkit",
+	"user.email=git-version-testkit@example.com",
+	"commit.gpgSign=false",
+	"tag.gpgSign=false",
+	"init.defaultBranch=main",
+	"protocol.file.allow=always",
+];
+
+impl Fixture {
+	/// Create an empty directory that is not a git repository.
+	///
+	/// The name of the directory contains spaces and non-ASCII characters,
+	/// to catch code that does not handle such paths correctly.
+	pub fn empty() -> Result<Self, String> {
+		let tempdir = tempfile::Builder::new()
+			.prefix("git-version-testkit ")
+			.tempdir()
+			.map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+		let dir = tempdir.path().join("fïxture");
+		std::fs::create_dir(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+		Ok(Self { tempdir, dir })
+	}
+
+	/// Create a new git repository without any commits.
+	pub fn init() -> Result<Self, String> {
+		let fixture = Self::empty()?;
+		fixture.git(&["init", "--quiet"])?;
+		Ok(fixture)
+	}
+
+	/// Create a shallow clone of this repository with only the latest commit.
+	pub fn shallow_clone(&self) -> Result<Self, String> {
+		let clone = Self::empty()?;
+		let url = format!("file://{}", self.dir.display());
+		clone.git(&["clone", "--quiet", "--depth=1", &url, "."])?;
+		Ok(clone)
+	}
+
+	/// Get the directory of the fixture.
+	pub fn path(&self) -> &Path {
+		&self.dir
+	}
+
+	/// Get a [`Repo`] to run git in the fixture.
+	///
+	/// Git does not read the global and system configuration files in the fixture.
+	pub fn repo(&self) -> Repo {
+		self.isolated_env().into_iter().fold(Repo::new(&self.dir), |repo, (key, value)| repo.with_env(key, value))
+	}
+
+	/// Get the environment variables that keep git from reading the global and system configuration files.
+	fn isolated_env(&self) -> [(&'static str, std::ffi::OsString); 2] {
+		// A missing global configuration file is treated as empty.
+		let global = self.tempdir.path().join("gitconfig");
+		[("GIT_CONFIG_GLOBAL", global.into_os_string()), ("GIT_CONFIG_NOSYSTEM", "1".into())]
+	}
+
+	/// Get a [`Repo`] for the fixture that can not find `git`, as if it is not installed.
+	pub fn repo_without_git(&self) -> Repo {
+		self.repo().with_env("PATH", "")
+	}
+
+	/// Run git in the fixture with the given arguments, and return its output.
+	pub fn git(&self, args: &[&str]) -> Result<String, String> {
+		let mut command = self.repo().command();
+		command.args(GIT_CONFIG.iter().flat_map(|x| ["-c", x])).args(args);
+		let output = command.output().map_err(|e| format!("Failed to run git: {}", e))?;
+		if !output.status.success() {
+			return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+		}
+		String::from_utf8(output.stdout)
+			.map(|x| x.trim_end().to_owned())
+			.map_err(|_| format!("git {} produced invalid UTF-8", args.join(" ")))
+	}
+
+	/// Write a file in the fixture, creating parent directories as needed.
+	pub fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), String> {
+		let path = self.dir.join(path);
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+		}
+		std::fs::write(&path, contents).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+	}
+
+	/// Commit all changes, including untracked files.
+	///
+	/// Each commit changes the `version.txt` file, so there is always something to commit.
+	pub fn commit(&self, message: &str) -> Result<(), String> {
+		self.write("version.txt", message)?;
+		self.git(&["add", "--all"])?;
+		self.git(&["commit", "--quiet", "-m", message])?;
+		Ok(())
+	}
+
+	/// Create an annotated tag for `HEAD`.
+	pub fn tag(&self, name: &str) -> Result<(), String> {
+		self.git(&["tag", "-a", "-m", name, name])?;
+		Ok(())
+	}
+
+	/// Create a lightweight tag for `HEAD`.
+	pub fn lightweight_tag(&self, name: &str) -> Result<(), String> {
+		self.git(&["tag", name])?;
+		Ok(())
+	}
+
+	/// Change a tracked file without committing it.
+	pub fn make_dirty(&self) -> Result<(), String> {
+		self.write("version.txt", "modified")
+	}
+
+	/// Create an untracked file.
+	pub fn add_untracked(&self, path: impl AsRef<Path>) -> Result<(), String> {
+		self.write(path, "untracked")
+	}
+
+	/// Write a binary crate that depends on `git-version` to the fixture, with the given contents of `src/main.rs`.
+	///
+	/// The `dependency` is the TOML value for the `git-version` dependency, such as `"0.3"` or `{ path = "../git-version" }`.
+	/// The crate is not committed.
+	pub fn write_crate(&self, dependency: &str, main: &str) -> Result<(), String> {
+		let manifest = format!(
+			"[package]\nname = \"fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\ngit-version = {}\n\n[workspace]\n",
+			dependency,
+		);
+		self.write("Cargo.toml", manifest)?;
+		self.write("src/main.rs", main)
+	}
+
+	/// Build and run the crate written by [`Fixture::write_crate()`] with `cargo run`, and return its output.
+	///
+	/// The `target_dir` can be shared between fixtures, so the dependencies are only built once.
+	/// Like for [`Fixture::repo()`], git does not read the global and system configuration files while the macros expand.
+	pub fn cargo_run(&self, target_dir: impl AsRef<Path>) -> Result<String, String> {
+		self.cargo_run_with_env(target_dir, [("", ""); 0])
+	}
+
+	/// Build and run the crate like [`Fixture::cargo_run()`], with additional environment variables for cargo.
+	///
+	/// This can be used to test how the macros handle variables like `SOURCE_DATE_EPOCH`.
+	pub fn cargo_run_with_env<I, K, V>(&self, target_dir: impl AsRef<Path>, env: I) -> Result<String, String>
+	where
+		I: IntoIterator<Item = (K, V)>,
+		K: AsRef<std::ffi::OsStr>,
+		V: AsRef<std::ffi::OsStr>,
+	{
+		let output = std::process::Command::new("cargo")
+			.current_dir(&self.dir)
+			.args(["run", "--quiet", "--target-dir"])
+			.arg(target_dir.as_ref())
+			.envs(self.isolated_env())
+			.envs(env)
+			.output()
+			.map_err(|e| format!("Failed to run cargo: {}", e))?;
+		if !output.status.success() {
+			return Err(format!("cargo run failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+		}
+		String::from_utf8(output.stdout).map_err(|_| "cargo run produced invalid UTF-8".to_owned())
+	}
+
+	/// Add another fixture as a submodule at the given path, and commit it.
+	pub fn add_submodule(&self, path: &str, submodule: &Fixture) -> Result<(), String> {
+		let url = format!("file://{}", submodule.dir.display());
+		self.git(&["submodule", "--quiet", "add", &url, path])?;
+		self.git(&["commit", "--quiet", "-m", &format!("Add submodule {}", path)])?;
+		Ok(())
+	}
+}
+
+#[test]
+fn test_states() {
+	use assert2::{assert, let_assert};
+
+	let args = ["--always", "--dirty=-modified"];
+	let_assert!(Ok(empty) = Fixture::empty());
+	assert!(let Err(_) = empty.repo().describe(args));
+
+	let_assert!(Ok(fixture) = Fixture::init());
+	let_assert!(Ok(()) = fixture.commit("one"));
+	let_assert!(Ok(scopes) = fixture.git(&["config", "--list", "--show-scope"]));
+	assert!(!scopes.lines().any(|x| x.starts_with("global") || x.starts_with("system")));
+	let_assert!(Ok(hash) = fixture.git(&["rev-parse", "--short", "HEAD"]));
+	assert!(fixture.repo().describe(args) == Ok(hash));
+	let_assert!(Err(e) = fixture.repo_without_git().describe(args));
+	assert!(e.contains("not found"));
+
+	let_assert!(Ok(()) = fixture.tag("v1.0"));
+	let_assert!(Ok(()) = fixture.commit("two"));
+	let_assert!(Ok(version) = fixture.repo().describe(args));
+	assert!(version.starts_with("v1.0-1-g"));
+
+	let_assert!(Ok(shallow) = fixture.shallow_clone());
+	let_assert!(Ok(version) = shallow.repo().describe(args));
+	assert!(!version.starts_with("v1.0"));
+
+	let_assert!(Ok(submodule) = Fixture::init());
+	let_assert!(Ok(()) = submodule.commit("sub"));
+	let_assert!(Ok(()) = fixture.add_submodule("vendor/sub", &submodule));
+	assert!(fixture.repo().submodules() == Ok(vec!["vendor/sub".to_owned()]));
+
+	let_assert!(Ok(()) = fixture.add_untracked("notes.txt"));
+	let_assert!(Ok(version) = fixture.repo().describe(args));
+	assert!(!version.ends_with("-modified"));
+	let_assert!(Ok(()) = fixture.make_dirty());
+	let_assert!(Ok(version) = fixture.repo().describe(args));
+	assert!(version.ends_with("-modified"));
+}
@@ -0,0 +1,101 @@
+//! Get the git version from a build script, with errors you can handle.
+//!
+//! The macros of this crate fail the build if git can not be run, unless a `fallback` is given.
+//! A build script can use this module instead, to decide for itself what to do when git is missing:
+//!
+//! ```no_run
+//! // build.rs
+//! let dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap();
+//! let version = match git_version::build::describe(dir, ["--always", "--dirty=-modified"]) {
+//!     Ok(version) => version,
+//!     Err(git_version::build::Error::NotInstalled) => String::from("unknown"),
+//!     Err(e) => panic!("{}", e),
+//! };
+//! println!("cargo:rustc-env=MY_VERSION={}", version);
+//! ```
+//!
+//! This module is only available with the `build` feature.
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::string::String;
+
+use git_version_core::git::Repo;
+
+/// An error from running git.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	/// The `git` command could not be found: git is not installed or not in `PATH`.
+	NotInstalled,
+
+	/// The directory is not inside a git repository.
+	NotARepository,
+
+	/// Git could not be run, or it ran but failed, with the given message.
+	Failed(String),
+}
+
+impl core::fmt::Display for Error {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Self::NotInstalled => write!(f, "git is not installed"),
+			Self::NotARepository => write!(f, "not a git repository"),
+			Self::Failed(message) => write!(f, "git failed: {}", message),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// Run `git describe` with the given arguments in a directory, and return its output.
+///
+/// Unlike the macros, this does not tell Cargo to rerun anything when the git state changes.
+/// Use `cargo:rerun-if-changed` in your build script for that.
+pub fn describe<I, S>(dir: impl AsRef<Path>, args: I) -> Result<String, Error>
+where
+	I: IntoIterator<Item = S>,
+	S: AsRef<OsStr>,
+{
+	// Go through the same code as the macros, so `--dirty` and `--broken` do not refresh the index.
+	// The commands use untranslated messages, so a missing repository can be recognized.
+	Repo::new(dir.as_ref()).describe(args).map_err(|message| {
+		if message.contains("not found: is git installed?") {
+			Error::NotInstalled
+		} else if message.contains("not a git repository") {
+			Error::NotARepository
+		} else {
+			Error::Failed(message)
+		}
+	})
+}
+
+#[test]
+fn test_describe() {
+	use assert2::{assert, let_assert};
+
+	let_assert!(Ok(tempdir) = tempfile::tempdir());
+	assert!(describe(tempdir.path(), ["--always"]) == Err(Error::NotARepository));
+
+	let manifest_dir = env!("CARGO_MANIFEST_DIR");
+	let_assert!(Ok(version) = describe(manifest_dir, ["--always"]));
+	assert!(!version.is_empty());
+	let_assert!(Err(Error::Failed(_)) = describe(manifest_dir, ["--no-such-option"]));
+
+	// The dirty marker is added once, also with `--broken`.
+	let git = |args: &[&str]| {
+		std::process::Command::new("git")
+			.current_dir(tempdir.path())
+			.args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "commit.gpgSign=false", "-c", "tag.gpgSign=false"])
+			.args(args)
+			.status()
+			.is_ok_and(|x| x.success())
+	};
+	assert!(git(&["init", "--quiet"]));
+	let_assert!(Ok(()) = std::fs::write(tempdir.path().join("file.txt"), "one"));
+	assert!(git(&["add", "."]));
+	assert!(git(&["commit", "--quiet", "-m", "Initial commit"]));
+	assert!(git(&["tag", "-a", "-m", "v1", "v1.0"]));
+	let_assert!(Ok(()) = std::fs::write(tempdir.path().join("file.txt"), "two"));
+	assert!(describe(tempdir.path(), ["--dirty=-dev", "--broken=-broken"]) == Ok("v1.0-dev".into()));
+	assert!(describe(tempdir.path(), ["--broken"]) == Ok("v1.0-dirty".into()));
+}
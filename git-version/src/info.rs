@@ -0,0 +1,48 @@
+/// Information about the git state of the source code, produced by [`git_version_info!()`](crate::git_version_info).
+///
+/// All fields are public, so the information can be used in `const` context:
+///
+/// ```
+/// use git_version::{git_version_info, VersionInfo};
+/// const INFO: VersionInfo = git_version_info!();
+/// const IS_RELEASE: bool = INFO.commits_since_tag == 0 && !INFO.dirty;
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VersionInfo {
+	/// The nearest annotated tag reachable from `HEAD`, if any.
+	pub tag: Option<&'static str>,
+
+	/// The number of commits since the tag, or `0` if there is no tag.
+	pub commits_since_tag: usize,
+
+	/// The full commit hash of `HEAD`.
+	pub sha: &'static str,
+
+	/// If true, the worktree had changes that were not committed.
+	pub dirty: bool,
+
+	/// The current branch, or `None` if `HEAD` was detached.
+	pub branch: Option<&'static str>,
+
+	/// The output of `git describe --always --dirty=-modified`, like [`git_version!()`](crate::git_version) gives by default.
+	pub describe: &'static str,
+}
+
+/// The fields of a [`VersionInfo`], in the order they are produced by the `git_version_info_parts!()` macro.
+#[doc(hidden)]
+pub type VersionInfoParts = (Option<&'static str>, usize, &'static str, bool, Option<&'static str>, &'static str);
+
+impl VersionInfo {
+	#[doc(hidden)]
+	pub const fn from_parts(parts: VersionInfoParts) -> Self {
+		let (tag, commits_since_tag, sha, dirty, branch, describe) = parts;
+		Self {
+			tag,
+			commits_since_tag,
+			sha,
+			dirty,
+			branch,
+			describe,
+		}
+	}
+}
@@ -13,6 +13,16 @@
 //! These macros do not depend on libgit, but simply uses the `git` binary directly.
 //! So you must have `git` installed somewhere in your `PATH`.
 //!
+//! With the `gix` feature, `git describe` and the list of submodules are computed with gitoxide instead,
+//! so the default `git_version!()` and `git_submodule_versions!()` also work where git is not installed.
+//! Other queries, `git describe` flags that gitoxide does not support, and the `env` and `inherit_git_env` arguments
+//! still use the `git` binary.
+//!
+//! The macros make Cargo rebuild the crate when the git state changes,
+//! by expanding to `include_bytes!()` of files in the `.git` directory.
+//! With the `nightly` feature, the unstable `proc_macro::tracked` API of a nightly compiler is used for this instead,
+//! which also tracks environment variables used by the macros without adding anything to the expansion.
+//!
 //! You can also get the version information for all submodules:
 //! ```
 //! use git_version::git_submodule_versions;
@@ -22,8 +32,88 @@
 //!     println!("{path}: {version}");
 //! }
 //! ```
+//!
+//! To look up the version of a submodule by path, use [`git_submodule_versions_map!()`] instead.
+//!
+//! Macros that expand to an array always sort the entries and remove duplicates,
+//! so the order does not depend on the git version or platform used to build your code.
+//!
+//! Macros that produce a string always expand to a `&'static str` that is evaluated at compile time,
+//! so they can initialize `const` and `static` items.
+//! To use one in a `match` pattern, assign it to a `const` item first.
+//!
+//! Git output is cached in memory, so macros that run the same git command in one compilation only run it once,
+//! and rust-analyzer does not run git on every keystroke.
+//! The cache is invalidated when the git metadata changes, so in rust-analyzer the dirty state may lag behind while editing.
+//! Set the `GIT_VERSION_CACHE` environment variable to `0` or `1` to force the cache off or on.
+//!
+//! If your build exports the worktree without its `.git` directory,
+//! write the path of the git directory to a file in the worktree, and set `GIT_VERSION_GITDIR_FILE` to the name of that file.
+//! The macros look for the file in the directory of the crate and its parents,
+//! and run git with `GIT_DIR` set to the path in the file.
+//!
+//! To audit which git state ended up in a build, set the `GIT_VERSION_REPORT` environment variable to `1`.
+//! Every macro expansion then appends a line of JSON to `git-version-report.ndjson` in the target directory,
+//! with the crate, macro, arguments, output, whether a fallback was used, and how long the expansion took.
+//! Set it to a path to write the report to a different file instead.
+//!
+//! With the `wasm` feature, `wasm_git_version!()` exports the version to JavaScript,
+//! so you can check which build of a WebAssembly module is loaded.
+
+#[cfg(feature = "build")]
+extern crate std;
+
+#[cfg(feature = "build")]
+pub mod build;
+
+mod info;
+pub use info::VersionInfo;
+
+mod parsed;
+pub use parsed::ParsedVersion;
+
+mod source;
+pub use source::VersionSource;
 
-pub use git_version_macro::{git_submodule_versions, git_version};
+mod submodules;
+pub use submodules::SubmoduleVersions;
+
+pub use git_version_macro::{
+	assert_tag_matches_cargo,
+	git_ahead_behind,
+	git_branch,
+	git_commit_author,
+	git_commit_committer,
+	git_commit_count,
+	git_commits_since_tag,
+	git_command,
+	git_commit_date,
+	git_commit_hash,
+	git_commit_message,
+	git_constants,
+	git_dirty,
+	git_dirty_files,
+	git_first_parent_count,
+	git_head_sha,
+	git_head_tags,
+	git_is_at_tag,
+	git_path_version,
+	git_release_channel,
+	git_remote_url,
+	git_sha_bytes,
+	git_sha_u128,
+	git_sha_u64,
+	git_signature_status,
+	git_submodule_versions,
+	git_trailer,
+	git_tree_hash,
+	git_version,
+	git_version_bytes,
+	git_version_command,
+	git_version_source,
+	git_workspace_versions,
+	try_git_version,
+};
 
 /// Run `git describe` at compile time with custom flags.
 ///
@@ -35,3 +125,104 @@ macro_rules! git_describe {
 		$crate::git_version!(args = [$($args)*])
 	};
 }
+
+/// Get the git version as a valid SemVer version, like `1.2.3` or `1.2.3-dev.4+gabc1234.dirty`.
+///
+/// This is a short-hand for `git_version!(semver = true, ...)`, see the `semver` argument of [`git_version!()`].
+/// All other arguments of [`git_version!()`] can be given too, except `format` and `dirty_cause`.
+///
+/// # Examples
+///
+/// ```
+/// # use git_version::git_version_semver;
+/// const VERSION: &str = git_version_semver!(fallback = "0.0.0-unknown");
+/// ```
+#[macro_export]
+macro_rules! git_version_semver {
+	($($args:tt)*) => {
+		$crate::git_version!(semver = true, $($args)*)
+	};
+}
+
+/// Get structured information about the git state of the source code.
+///
+/// This macro expands to a [`VersionInfo`] with the nearest tag, the number of commits since that tag,
+/// the commit hash, the dirty state, the current branch, and the output of `git describe`,
+/// so you do not have to take apart the version string yourself.
+///
+/// The `env`, `inherit_git_env`, `allow_unsafe_directory`, `budget_ms` and `track` arguments can be given, like for [`git_version!()`].
+///
+/// # Examples
+///
+/// ```
+/// # use git_version::{git_version_info, VersionInfo};
+/// const INFO: VersionInfo = git_version_info!();
+/// println!("{} ({} commits since {:?})", INFO.sha, INFO.commits_since_tag, INFO.tag);
+/// ```
+#[macro_export]
+macro_rules! git_version_info {
+	($($args:tt)*) => {
+		$crate::VersionInfo::from_parts($crate::__private::git_version_info_parts!($($args)*))
+	};
+}
+
+/// Get the git version of all submodules as a [`SubmoduleVersions`] lookup table.
+///
+/// This macro accepts the same arguments as [`git_submodule_versions!()`], except `urls`, `static_ref` and `as_slice`.
+/// The version of a submodule can then be looked up by path with [`SubmoduleVersions::get()`],
+/// which uses a binary search instead of a linear scan over the array, and also works in `const` context.
+///
+/// # Examples
+///
+/// ```
+/// # use git_version::{git_submodule_versions_map, SubmoduleVersions};
+/// const SUBMODULES: SubmoduleVersions = git_submodule_versions_map!(fallback = "unknown");
+/// if let Some(version) = SUBMODULES.get("vendor/foo") {
+///     println!("vendor/foo: {version}");
+/// }
+/// ```
+#[macro_export]
+macro_rules! git_submodule_versions_map {
+	($($args:tt)*) => {
+		$crate::SubmoduleVersions::new($crate::git_submodule_versions!(as_slice = true, $($args)*))
+	};
+}
+
+/// Export a `git_version()` function to JavaScript using `wasm-bindgen`.
+///
+/// The function returns the git version as a string.
+/// This macro accepts the same arguments as [`git_version!()`], which are used to get the version.
+///
+/// This macro is only available with the `wasm` feature.
+/// You do not need to depend on `wasm-bindgen` yourself.
+///
+/// # Examples
+///
+/// ```
+/// git_version::wasm_git_version!(prefix = "git:", fallback = "unknown");
+/// ```
+///
+/// From JavaScript:
+///
+/// ```js
+/// import { git_version } from "./my_module.js";
+/// console.log(`loaded ${git_version()}`);
+/// ```
+#[cfg(feature = "wasm")]
+#[macro_export]
+macro_rules! wasm_git_version {
+	($($args:tt)*) => {
+		#[$crate::__private::wasm_bindgen::prelude::wasm_bindgen(wasm_bindgen = $crate::__private::wasm_bindgen)]
+		pub fn git_version() -> ::std::string::String {
+			::std::string::String::from($crate::git_version!($($args)*))
+		}
+	};
+}
+
+#[doc(hidden)]
+pub mod __private {
+	pub use git_version_macro::git_version_info_parts;
+
+	#[cfg(feature = "wasm")]
+	pub use wasm_bindgen;
+}
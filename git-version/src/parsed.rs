@@ -0,0 +1,143 @@
+/// The components of a version string produced by `git describe`.
+///
+/// A version like `v1.2-3-gabcdef1-modified` consists of the nearest tag (`v1.2`),
+/// the number of commits since that tag (`3`), the abbreviated commit hash (`abcdef1`),
+/// and a marker that the worktree was dirty (`-modified`).
+///
+/// The fields are public, so the structure can be constructed and matched on in `const` context,
+/// and [`ParsedVersion::parse()`] is a `const fn`:
+///
+/// ```
+/// use git_version::{git_version, ParsedVersion};
+/// const VERSION: ParsedVersion = ParsedVersion::parse(git_version!());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ParsedVersion<'a> {
+	/// The nearest tag, if any.
+	pub tag: Option<&'a str>,
+
+	/// The number of commits since the tag, or `0` if the commit is tagged or there is no tag.
+	pub distance: usize,
+
+	/// The abbreviated commit hash, if it is part of the version.
+	///
+	/// `git describe` only includes the hash if the commit is not tagged, or when `--long` or `--always` are used.
+	pub hash: Option<&'a str>,
+
+	/// If true, the version ends with a dirty marker.
+	pub dirty: bool,
+}
+
+impl<'a> ParsedVersion<'a> {
+	/// Split a version string produced by `git describe` into its components.
+	///
+	/// Both the `-modified` marker used by [`git_version!()`](crate::git_version) and the `-dirty` marker used by git are recognized.
+	/// Use [`ParsedVersion::parse_with_dirty_marker()`] if you passed a custom marker to `--dirty`.
+	///
+	/// Parsing never fails: a version that does not end in a distance and hash is taken to be just a tag,
+	/// unless it consists of at least 7 lowercase hexadecimal digits, in which case it is taken to be a hash.
+	pub const fn parse(version: &'a str) -> Self {
+		if ends_with(version.as_bytes(), b"-modified") {
+			Self::parse_with_dirty_marker(version, "-modified")
+		} else {
+			Self::parse_with_dirty_marker(version, "-dirty")
+		}
+	}
+
+	/// Split a version string produced by `git describe` into its components, using a custom dirty marker.
+	///
+	/// The marker must be the same as given to the `--dirty` flag of `git describe`.
+	/// An empty marker never matches.
+	pub const fn parse_with_dirty_marker(version: &'a str, marker: &str) -> Self {
+		let mut bytes = version.as_bytes();
+		let dirty = !marker.is_empty() && ends_with(bytes, marker.as_bytes());
+		if dirty {
+			bytes = bytes.split_at(bytes.len() - marker.len()).0;
+		}
+
+		// Look for a `-<distance>-g<hash>` suffix.
+		let hash_start = bytes.len() - count_from_end(bytes, true);
+		if hash_start < bytes.len() && hash_start >= 2 && bytes[hash_start - 1] == b'g' && bytes[hash_start - 2] == b'-' {
+			let (head, hash) = bytes.split_at(hash_start);
+			let head = head.split_at(head.len() - 2).0;
+			let digits = count_from_end(head, false);
+			let distance_start = head.len() - digits;
+			if digits > 0 && distance_start > 0 && head[distance_start - 1] == b'-' {
+				let (tag, distance) = head.split_at(distance_start);
+				let tag = tag.split_at(tag.len() - 1).0;
+				return Self {
+					tag: Some(to_str(tag)),
+					distance: parse_usize(distance),
+					hash: Some(to_str(hash)),
+					dirty,
+				};
+			}
+		}
+
+		if bytes.len() >= 7 && hash_start == 0 {
+			Self {
+				tag: None,
+				distance: 0,
+				hash: Some(to_str(bytes)),
+				dirty,
+			}
+		} else {
+			Self {
+				tag: Some(to_str(bytes)),
+				distance: 0,
+				hash: None,
+				dirty,
+			}
+		}
+	}
+}
+
+/// Check if a byte string ends with a suffix.
+const fn ends_with(bytes: &[u8], suffix: &[u8]) -> bool {
+	if suffix.len() > bytes.len() {
+		return false;
+	}
+	let offset = bytes.len() - suffix.len();
+	let mut i = 0;
+	while i < suffix.len() {
+		if bytes[offset + i] != suffix[i] {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+/// Count the number of digits at the end of a byte string.
+///
+/// If `hex` is true, lowercase hexadecimal digits (as used in commit hashes) are counted, otherwise only decimal digits.
+const fn count_from_end(bytes: &[u8], hex: bool) -> usize {
+	let mut count = 0;
+	while count < bytes.len() {
+		let byte = bytes[bytes.len() - 1 - count];
+		if !(byte.is_ascii_digit() || hex && matches!(byte, b'a'..=b'f')) {
+			break;
+		}
+		count += 1;
+	}
+	count
+}
+
+/// Parse a string of ASCII digits, saturating on overflow.
+const fn parse_usize(digits: &[u8]) -> usize {
+	let mut value: usize = 0;
+	let mut i = 0;
+	while i < digits.len() {
+		value = value.saturating_mul(10).saturating_add((digits[i] - b'0') as usize);
+		i += 1;
+	}
+	value
+}
+
+/// Convert a byte string that was split from a `&str` at ASCII characters back to a `&str`.
+const fn to_str(bytes: &[u8]) -> &str {
+	match core::str::from_utf8(bytes) {
+		Ok(x) => x,
+		Err(_) => panic!("split inside of a UTF-8 character"),
+	}
+}
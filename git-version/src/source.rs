@@ -0,0 +1,57 @@
+/// Where the version embedded by [`git_version!()`](crate::git_version) came from.
+///
+/// Use [`git_version_source!()`](crate::git_version_source) with the same arguments as `git_version!()`
+/// to get the source of the version, and [`VersionSource::parse()`] to turn it into this enum:
+///
+/// ```
+/// use git_version::{git_version_source, VersionSource};
+/// const SOURCE: VersionSource = VersionSource::parse(git_version_source!(fallback = "unknown"));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VersionSource {
+	/// The version was produced by git.
+	Git,
+
+	/// The version was taken from the environment variable given by the `env_override` argument.
+	Env,
+
+	/// The version was made from the `.cargo_vcs_info.json` file of a packaged crate, possibly using `packaged_format`.
+	Packaged,
+
+	/// The version was read from the `.git_archival.txt` file of an archive made by `git archive`.
+	Archive,
+
+	/// The version was read from the file given by the `fallback_file` argument.
+	File,
+
+	/// Cargo's version was used, because of the `cargo_prefix` or `cargo_suffix` arguments.
+	Cargo,
+
+	/// The `fallback` argument was used.
+	Fallback,
+}
+
+impl VersionSource {
+	/// Get the source from the string produced by [`git_version_source!()`](crate::git_version_source).
+	///
+	/// # Panics
+	/// Panics if the string is not produced by `git_version_source!()`.
+	/// When used to initialize a constant, this is a compile error instead.
+	pub const fn parse(tag: &str) -> Self {
+		match tag.as_bytes() {
+			b"git" => Self::Git,
+			b"env" => Self::Env,
+			b"packaged" => Self::Packaged,
+			b"archive" => Self::Archive,
+			b"file" => Self::File,
+			b"cargo" => Self::Cargo,
+			b"fallback" => Self::Fallback,
+			_ => panic!("unknown version source"),
+		}
+	}
+
+	/// Check if the version was produced by git, rather than one of the fallbacks.
+	pub const fn is_git(self) -> bool {
+		matches!(self, Self::Git)
+	}
+}
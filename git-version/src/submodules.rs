@@ -0,0 +1,85 @@
+use core::cmp::Ordering;
+
+/// The versions of the submodules, produced by [`git_submodule_versions_map!()`](crate::git_submodule_versions_map).
+///
+/// The version of a submodule can be looked up by path with a binary search, also in `const` context:
+///
+/// ```
+/// use git_version::{git_submodule_versions_map, SubmoduleVersions};
+/// const SUBMODULES: SubmoduleVersions = git_submodule_versions_map!(fallback = "unknown");
+/// const FIRMWARE: Option<&str> = SUBMODULES.get("firmware");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubmoduleVersions {
+	entries: &'static [(&'static str, &'static str)],
+}
+
+impl SubmoduleVersions {
+	/// Create a lookup table from `(path, version)` entries.
+	///
+	/// # Panics
+	/// Panics if the entries are not sorted by the byte-wise order of their path, or if a path appears more than once.
+	/// The entries produced by [`git_submodule_versions!()`](crate::git_submodule_versions) are always sorted this way.
+	/// When used to initialize a constant, this is a compile error instead.
+	pub const fn new(entries: &'static [(&'static str, &'static str)]) -> Self {
+		let mut i = 1;
+		while i < entries.len() {
+			if !matches!(compare(entries[i - 1].0, entries[i].0), Ordering::Less) {
+				panic!("submodule versions must be sorted by path without duplicates");
+			}
+			i += 1;
+		}
+		Self { entries }
+	}
+
+	/// Get the version of the submodule at the given path, or `None` if there is no such submodule.
+	///
+	/// The path must be written exactly like in the entries, such as `vendor/foo`.
+	pub const fn get(&self, path: &str) -> Option<&'static str> {
+		let (mut low, mut high) = (0, self.entries.len());
+		while low < high {
+			let middle = low + (high - low) / 2;
+			let (key, version) = self.entries[middle];
+			match compare(key, path) {
+				Ordering::Less => low = middle + 1,
+				Ordering::Greater => high = middle,
+				Ordering::Equal => return Some(version),
+			}
+		}
+		None
+	}
+
+	/// Get all `(path, version)` entries, sorted by path.
+	pub const fn entries(&self) -> &'static [(&'static str, &'static str)] {
+		self.entries
+	}
+
+	/// Get the number of submodules.
+	pub const fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Check if there are no submodules.
+	pub const fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+/// Compare two strings by their bytes, like `Ord for str`, but usable in `const` context.
+const fn compare(a: &str, b: &str) -> Ordering {
+	let (a, b) = (a.as_bytes(), b.as_bytes());
+	let mut i = 0;
+	while i < a.len() && i < b.len() {
+		if a[i] != b[i] {
+			return if a[i] < b[i] { Ordering::Less } else { Ordering::Greater };
+		}
+		i += 1;
+	}
+	if a.len() < b.len() {
+		Ordering::Less
+	} else if a.len() > b.len() {
+		Ordering::Greater
+	} else {
+		Ordering::Equal
+	}
+}
@@ -1,7 +1,14 @@
 use std::path::Path;
 
 use assert2::{assert, let_assert};
-use git_version::{git_describe, git_submodule_versions, git_version};
+use git_version::{git_ahead_behind, git_branch, git_commit_hash, git_describe, git_first_parent_count, git_submodule_versions, git_trailer};
+use git_version::{git_command, git_commit_date, git_dirty, git_release_channel, git_version, git_version_bytes, git_version_source};
+use git_version::{git_commit_count, git_commits_since_tag, git_path_version, git_version_command, git_version_semver};
+use git_version::{git_commit_author, git_commit_committer, git_commit_message, git_head_sha, git_head_tags, git_remote_url};
+use git_version::{git_dirty_files, git_is_at_tag, git_signature_status, git_tree_hash, git_workspace_versions, try_git_version};
+use git_version::{git_sha_bytes, git_sha_u128, git_sha_u64, git_submodule_versions_map};
+use git_version::{ParsedVersion, SubmoduleVersions, VersionInfo, VersionSource};
+use git_version_testkit::Fixture;
 
 #[test]
 fn git_describe_is_right() {
@@ -16,7 +23,685 @@ fn git_describe_is_right() {
 	assert!(git_version!(args = ["--always", "--dirty=-modified"]) == name);
 	assert!(git_describe!("--always", "--dirty=-modified") == name);
 	assert!(git_version!(prefix = "[", suffix = "]") == format!("[{}]", name));
+	assert!(git_version_bytes!(prefix = "[", suffix = "]") == format!("[{}]", name).as_bytes());
+	assert!(git_version!(prefix = "[", max_len = 4, overflow = "truncate") == format!("[{}", &name[..3]));
+	assert!(git_version!(combine_format = "{cargo}+{git}") == format!("{}+{}", env!("CARGO_PKG_VERSION"), name));
+	assert!(git_version!(prefix = "git:", validate = "^git:.") == format!("git:{}", name));
+	assert!(git_version!(prefix = "a_b/", replace = [("_", "-"), ("/", ".")]) == format!("a-b.{}", name));
+	assert!(!git_version!(refs = "all").starts_with("heads/"));
+	assert!(git_version!(untracked = "no") == name);
+	assert!(git_version!(repo = "current") == name);
+	assert!(git_version!(path = "../git-version-core") == name);
+	assert!(git_version!(path = "/", fallback = "none") == "none");
+	assert!(git_version!(relative_to = "source") == name);
+	assert!(git_version!(relative_to = "source", path = "../../git-version-core") == name);
+	assert!(git_version!(packaged_format = "{cargo} ({hash}, from package)") == name);
+	assert!(git_version!(repo = "superproject", fallback = "none") == "none");
+	assert!(git_version!(env_override = "GIT_VERSION_UNSET_OVERRIDE", suffix = "!") == format!("{}!", name));
+	assert!(git_version!(env_override = "CARGO_PKG_NAME", suffix = "!") == "git-version");
+	assert!(git_version!(sources = [env("GIT_VERSION_UNSET_OVERRIDE"), git, "unknown"]) == name);
+	assert!(git_version!(sources = [env("CARGO_PKG_NAME"), git]) == "git-version");
+	assert!(git_version!(env = { GIT_DIR = "/nonexistent" }, sources = [git, "unknown"], fallback = "none") == "unknown");
+	assert!(git_version!(env = { GIT_DIR = "/nonexistent" }, sources = [git, archival], fallback = "none") == "none");
+	assert!(git_version!(fallback_env = "CARGO_PKG_NAME") == name);
+	assert!(git_version!(env = { GIT_DIR = "/nonexistent" }, fallback_env = "CARGO_PKG_NAME") == "git-version");
+	let version = git_version!(env = { GIT_DIR = "/nonexistent" }, fallback_file = "Cargo.toml", max_len = 10, overflow = "truncate");
+	assert!(version == "[package]\n");
+	assert!(git_version!(sources = [file("nonexistent"), "unknown"], prefix = "v") == "unknown");
+	assert!(git_version!(check = "error") == git_version!());
+	assert!(git_version!(target_suffix = { lib = "+lib", test = "+test" }, suffix = "!") == format!("{}+test!", name));
 	assert!(git_submodule_versions!() == []);
+	assert!(git_submodule_versions!(paths = ["vendor/*", "firmware"]) == []);
+	let modules: [(&str, &str, &str); 0] = git_submodule_versions!(urls = true);
+	assert!(modules.is_empty());
+	let modules: [(&str, &str); 0] = git_submodule_versions!(out_of_sync = "+");
+	assert!(modules.is_empty());
+	let modules: [(&str, &str); 0] = git_submodule_versions!(overrides = {});
+	assert!(modules.is_empty());
+	const MODULES: &[(&str, &str)] = git_submodule_versions!(static_ref = true);
+	assert!(MODULES.is_empty());
+	const SLICE: &[(&str, &str)] = git_submodule_versions!(as_slice = true);
+	assert!(SLICE.is_empty());
+	static STATIC_SLICE: &[(&str, &str)] = git_submodule_versions!(as_slice = true, static_ref = true);
+	assert!(STATIC_SLICE.is_empty());
+}
+
+#[test]
+fn git_version_format_is_right() {
+	let output = std::process::Command::new("git")
+		.args(["describe", "--long", "--always", "--dirty=-modified"])
+		.output()
+		.expect("failed to execute git")
+		.stdout;
+
+	let_assert!(Ok(name) = std::str::from_utf8(&output));
+	let parsed = ParsedVersion::parse(name.trim());
+	let_assert!(Some(hash) = parsed.hash);
+	let expected = format!("{}+{}.{}{}", parsed.tag.unwrap_or(""), parsed.distance, hash, if parsed.dirty { "-dev" } else { "" });
+	assert!(git_version!(format = "{tag}+{commits}.{sha}{dirty:-dev}") == expected);
+	assert!(git_version!(format = "{{{sha}{dirty}}}", prefix = "git:") == format!("git:{{{}}}", name.trim().rsplit("-g").next().unwrap()));
+}
+
+#[test]
+fn string_macros_are_constants() {
+	const VERSION: &str = git_version!();
+	static DATE: &str = git_commit_date!();
+	const CHANNEL: &str = git_release_channel!(rules = [("tag:no-such-tag-*", "stable")], fallback = concat!("night", "ly"));
+	const TRAILER: &str = git_trailer!(key = "No-Such-Trailer", fallback = CHANNEL);
+	assert!(matches!(git_version!(), VERSION));
+	assert!(DATE.len() == "2000-01-01".len());
+	assert!(TRAILER == "nightly");
+	assert!(concat!("[", git_trailer!(key = "No-Such-Trailer", fallback = "none"), "]") == "[none]");
+}
+
+const fn or_unknown(version: &'static str) -> &'static str {
+	if version.is_empty() { "unknown" } else { version }
+}
+
+const fn or_unknown_bytes(version: &'static [u8]) -> &'static [u8] {
+	if version.is_empty() { b"unknown" } else { version }
+}
+
+#[test]
+fn git_version_post_processes() {
+	const VERSION: &str = git_version!(env = { GIT_DIR = "/nonexistent" }, fallback = "", post = or_unknown);
+	assert!(VERSION == "unknown");
+	assert!(git_version!(post = self::or_unknown) == git_version!());
+	assert!(git_version_bytes!(env = { GIT_DIR = "/nonexistent" }, fallback = "", post = or_unknown_bytes) == b"unknown");
+}
+
+#[test]
+fn git_version_bytes_limits_byte_string_fallback() {
+	let version = git_version_bytes!(env = { GIT_DIR = "/nonexistent" }, fallback = b"unknown", max_len = 4, overflow = "truncate");
+	assert!(version == b"unkn");
+	let version = git_version_bytes!(env = { GIT_DIR = "/nonexistent" }, fallback = b"a_b", replace = [("_", "-")]);
+	assert!(version == b"a-b");
+}
+
+mod constants {
+	git_version::git_constants! {
+		VERSION = version(),
+		HASH = hash(),
+		SHORT_HASH = short_hash(),
+		BRANCH = branch(),
+		DATE = commit_date(),
+		DIRTY = dirty(),
+	}
+}
+
+#[test]
+fn git_constants_are_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["rev-parse", "HEAD"]).output());
+	assert!(constants::HASH == String::from_utf8_lossy(&output.stdout).trim());
+	assert!(constants::HASH.starts_with(constants::SHORT_HASH));
+	assert!(git_commit_hash!() == constants::HASH);
+	assert!(constants::HASH.starts_with(git_commit_hash!(short = 12)));
+	assert!(git_commit_hash!(short = 12).len() >= 12);
+	assert!(git_commit_hash!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+	assert!(constants::VERSION == git_version!());
+	assert!(constants::DATE == git_commit_date!());
+	assert!(constants::DIRTY == constants::VERSION.ends_with("-modified"));
+	assert!(git_dirty!() == constants::DIRTY);
+	const DIRTY: bool = git_dirty!(env = { GIT_DIR = "/nonexistent" }, fallback = true);
+	assert!(DIRTY);
+	assert!(!constants::BRANCH.is_empty());
+	assert!(git_branch!(detached = "HEAD") == constants::BRANCH);
+	assert!(git_branch!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_constants_in_fixture() {
+	// A tag ending in the dirty suffix does not make a clean worktree dirty.
+	let_assert!(Ok(fixture) = Fixture::init());
+	let dependency = format!("{{ path = {:?} }}", env!("CARGO_MANIFEST_DIR"));
+	let main = r#"
+		git_version::git_constants! {
+			DIRTY = dirty(),
+			DATE = commit_date(),
+			VERSION = version(),
+		}
+		fn main() {
+			println!("{} {} {} {}", DIRTY, DATE, VERSION, git_version::git_commit_date!());
+		}
+	"#;
+	let_assert!(Ok(()) = fixture.write_crate(&dependency, main));
+	let_assert!(Ok(()) = fixture.commit("Initial commit"));
+	let_assert!(Ok(()) = fixture.tag("v3-modified"));
+	let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures");
+	let_assert!(Ok(output) = fixture.cargo_run_with_env(&target_dir, [("SOURCE_DATE_EPOCH", "86400")]));
+	assert!(output.trim() == "false 1970-01-02 v3-modified 1970-01-02");
+
+	let_assert!(Ok(()) = fixture.make_dirty());
+	let_assert!(Ok(output) = fixture.cargo_run_with_env(&target_dir, [("SOURCE_DATE_EPOCH", "")]));
+	let fields: Vec<_> = output.split_whitespace().collect();
+	assert!(fields[0] == "true");
+	assert!(fields[1] == fields[3]);
+	assert!(fields[1] != "1970-01-02");
+	assert!(fields[2] == "v3-modified-modified");
+}
+
+#[test]
+fn git_branch_detached_in_fixture() {
+	let_assert!(Ok(fixture) = Fixture::init());
+	let dependency = format!("{{ path = {:?} }}", env!("CARGO_MANIFEST_DIR"));
+	let main = r#"
+		fn main() {
+			println!("{}", git_version::git_branch!(detached = short_hash));
+			println!("{}", git_version::git_branch!(detached = short_hash, short = 12));
+		}
+	"#;
+	let_assert!(Ok(()) = fixture.write_crate(&dependency, main));
+	let_assert!(Ok(()) = fixture.commit("Initial commit"));
+	let_assert!(Ok(_) = fixture.git(&["checkout", "--quiet", "--detach"]));
+	let_assert!(Ok(_) = fixture.git(&["config", "core.abbrev", "9"]));
+	let_assert!(Ok(hash) = fixture.git(&["rev-parse", "HEAD"]));
+	let hash = hash.trim();
+	let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures");
+	let_assert!(Ok(output) = fixture.cargo_run(&target_dir));
+	let lines: Vec<_> = output.lines().collect();
+	assert!(lines == [&hash[..9], &hash[..12]]);
+}
+
+#[test]
+fn git_version_source_is_right() {
+	const SOURCE: VersionSource = VersionSource::parse(git_version_source!());
+	assert!(SOURCE == VersionSource::Git);
+	assert!(SOURCE.is_git());
+	let source = VersionSource::parse(git_version_source!(env = { GIT_DIR = "/nonexistent" }, cargo_prefix = "cargo:"));
+	assert!(source == VersionSource::Cargo);
+	let source = VersionSource::parse(git_version_source!(env = { GIT_DIR = "/nonexistent" }, fallback = "none"));
+	assert!(source == VersionSource::Fallback);
+	let source = VersionSource::parse(git_version_source!(env_override = "CARGO_PKG_NAME"));
+	assert!(source == VersionSource::Env);
+	let source = VersionSource::parse(git_version_source!(sources = [env("GIT_VERSION_UNSET_OVERRIDE"), cargo, git]));
+	assert!(source == VersionSource::Cargo);
+}
+
+#[test]
+fn git_version_match_and_exclude() {
+	const NO_TAGS: &str = git_version!(args = ["--always", "--match=no-such-tag-*"]);
+	assert!(git_version!(args = ["--always"], match = "no-such-tag-*") == NO_TAGS);
+	assert!(git_version!(args = ["--always"], exclude = "*") == NO_TAGS);
+	assert!(git_version!(args = ["--always"], match = ["a-*", "b-*"], exclude = ["*"]) == NO_TAGS);
+	assert!(git_version!(match = "*") == git_version!());
+	const COMMAND: &str = git_version_command!(match = "v*", exclude = ["*-rc*"]);
+	assert!(COMMAND.ends_with(" describe --always --dirty=-modified '--match=v*' '--exclude=*-rc*'"));
+}
+
+#[test]
+fn git_version_abbrev() {
+	assert!(git_version!(abbrev = 12) == git_version!(args = ["--always", "--dirty=-modified", "--abbrev=12"]));
+	assert!(git_version!(args = ["--always", "--abbrev=20"], abbrev = 9) == git_version!(args = ["--always", "--abbrev=9"]));
+	const FULL: &str = git_version!(args = ["--always", "--match=no-such-tag-*"], abbrev = 40);
+	assert!(FULL.starts_with(git_commit_hash!()));
+	const COMMAND: &str = git_version_command!(abbrev = 12);
+	assert!(COMMAND.ends_with(" describe --always --dirty=-modified --abbrev=12"));
+}
+
+#[test]
+fn git_version_dirty_and_broken_suffix() {
+	const COMMAND: &str = git_version_command!(dirty = "-dev", broken = "-broken");
+	assert!(COMMAND.ends_with(" describe --always --dirty=-modified --dirty=-dev --broken=-broken"));
+
+	// This repository may be clean, so check the suffixes in a crate in a dirty fixture repository.
+	let_assert!(Ok(fixture) = Fixture::init());
+	let dependency = format!("{{ path = {:?} }}", env!("CARGO_MANIFEST_DIR"));
+	let main = r#"
+		use git_version::git_version;
+		fn main() {
+			println!("{}", git_version!(dirty = "-dev"));
+			println!("{}", git_version!(broken = "-broken"));
+			println!("{}", git_version!(dirty = "-dev", broken = "-broken"));
+			println!("{}", git_version!(args = ["--always", "--broken"]));
+			println!("{}", git_version!(args = ["--always", "--dirty=-x"], dirty = "-y"));
+			println!("{}", git_version!(broken = "-broken", dirty_exclude = ["no-such-file"]));
+		}
+	"#;
+	let_assert!(Ok(()) = fixture.write_crate(&dependency, main));
+	let_assert!(Ok(()) = fixture.commit("Initial commit"));
+	let_assert!(Ok(()) = fixture.tag("v1.0"));
+	let_assert!(Ok(()) = fixture.make_dirty());
+	let_assert!(Ok(output) = fixture.cargo_run(Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures")));
+	let lines: Vec<_> = output.lines().collect();
+	assert!(lines == ["v1.0-dev", "v1.0-modified", "v1.0-dev", "v1.0-dirty", "v1.0-y", "v1.0-modified"]);
+}
+
+#[test]
+fn git_version_semver_is_valid() {
+	const VERSION: &str = git_version_semver!();
+	assert!(VERSION == git_version!(semver = true));
+	assert!(git_version_semver!(fallback = "0.0.0-unknown") == VERSION);
+
+	let (version, build) = VERSION.split_once('+').unwrap_or((VERSION, ""));
+	let (core, pre) = version.split_once('-').unwrap_or((version, ""));
+	let numbers: Vec<&str> = core.split('.').collect();
+	assert!(numbers.len() == 3);
+	assert!(numbers.iter().all(|x| x.parse::<u64>().is_ok_and(|n| n.to_string() == *x)));
+	for identifier in pre.split('.').filter(|_| !pre.is_empty()).chain(build.split('.').filter(|_| !build.is_empty())) {
+		assert!(!identifier.is_empty());
+		assert!(identifier.bytes().all(|x| x.is_ascii_alphanumeric() || x == b'-'));
+	}
+
+	let describe = git_version!(args = ["--always", "--long", "--dirty=-modified"]);
+	let parsed = ParsedVersion::parse(describe);
+	let_assert!(Some(hash) = parsed.hash);
+	assert!(build.contains(&format!("g{}", hash)) || (parsed.distance == 0 && !parsed.dirty));
+	assert!(build.ends_with("dirty") == parsed.dirty);
+}
+
+#[test]
+fn git_version_patch_id() {
+	use std::process::{Command, Stdio};
+	let_assert!(Ok(diff) = Command::new("git").args(["diff", "HEAD"]).output());
+	let_assert!(Ok(mut child) = Command::new("git").args(["patch-id", "--stable"]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn());
+	let_assert!(Some(mut stdin) = child.stdin.take());
+	let_assert!(Ok(()) = std::io::Write::write_all(&mut stdin, &diff.stdout));
+	drop(stdin);
+	let_assert!(Ok(output) = child.wait_with_output());
+	let output = String::from_utf8_lossy(&output.stdout);
+	let patch_id = output.split(' ').next().unwrap_or_default();
+	let short_id = &patch_id[..patch_id.len().min(7)];
+
+	const VERSION: &str = git_version!(dirty = "-dirty.{patch_id}");
+	const PLAIN: &str = git_version!(dirty = "-dirty.");
+	assert!(VERSION == format!("{}{}", PLAIN, if PLAIN.ends_with("-dirty.") { short_id } else { "" }));
+	let_assert!(Some((sha, _)) = git_version!(format = "{sha} {patch_id}").split_once(' '));
+	assert!(git_version!(format = "{sha} {patch_id}") == format!("{} {}", sha, short_id));
+	assert!(git_version!(format = "{sha}{dirty}", dirty = "+{patch_id}") == git_version!(format = "{sha}{dirty:+}{patch_id}"));
+}
+
+#[test]
+fn git_version_without_tracking() {
+	const VERSION: &str = git_version!(track = false);
+	assert!(VERSION == git_version!());
+	assert!(git_commit_hash!(track = false) == git_commit_hash!());
+	assert!(git_submodule_versions!(track = false) == []);
+}
+
+#[test]
+fn git_version_allow_unsafe_directory() {
+	assert!(git_version!(allow_unsafe_directory = true) == git_version!());
+	assert!(git_commit_hash!(allow_unsafe_directory = true) == git_commit_hash!());
+}
+
+#[test]
+fn git_version_dirty_exclude() {
+	assert!(!git_dirty!(dirty_exclude = ["*"]));
+	assert!(!git_version!(dirty_exclude = ["*"]).ends_with("-modified"));
+	assert!(git_version!(dirty_exclude = ["no-such-file"]) == git_version!());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn git_version_require_clean_in_release() {
+	// With debug assertions, a dirty worktree does not fail the build.
+	assert!(git_version!(require_clean = "release") == git_version!());
+	assert!(git_version!(require_clean = false) == git_version!());
+}
+
+#[test]
+fn git_version_info_is_right() {
+	const INFO: VersionInfo = git_version::git_version_info!();
+	assert!(INFO.describe == git_version!());
+	assert!(INFO.sha == git_commit_hash!());
+	assert!(INFO.dirty == git_dirty!());
+	assert!(INFO.branch == (constants::BRANCH != "HEAD").then_some(constants::BRANCH));
+	let parsed = ParsedVersion::parse(INFO.describe);
+	if parsed.hash.is_some() {
+		assert!(INFO.tag == parsed.tag);
+		assert!(INFO.commits_since_tag == parsed.distance);
+	}
+}
+
+#[test]
+fn git_version_info_in_fixture() {
+	// A tag ending in the dirty suffix does not make a clean worktree dirty.
+	let_assert!(Ok(fixture) = Fixture::init());
+	let dependency = format!("{{ path = {:?} }}", env!("CARGO_MANIFEST_DIR"));
+	let main = r#"
+		fn main() {
+			const INFO: git_version::VersionInfo = git_version::git_version_info!();
+			println!("{} {}", INFO.dirty, INFO.describe);
+		}
+	"#;
+	let_assert!(Ok(()) = fixture.write_crate(&dependency, main));
+	let_assert!(Ok(()) = fixture.commit("Initial commit"));
+	let_assert!(Ok(()) = fixture.tag("v3-modified"));
+	let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures");
+	assert!(fixture.cargo_run(&target_dir).as_deref().map(str::trim) == Ok("false v3-modified"));
+	// Changes in the worktree do not trigger a rebuild, so write the crate again to update its modification time.
+	let_assert!(Ok(()) = fixture.make_dirty());
+	let_assert!(Ok(()) = fixture.write_crate(&dependency, main));
+	assert!(fixture.cargo_run(&target_dir).as_deref().map(str::trim) == Ok("true v3-modified-modified"));
+}
+
+#[test]
+fn git_command_is_right() {
+	assert!(git_command!(args = ["rev-parse", "HEAD"]) == git_commit_hash!());
+	assert!(git_command!(args = ["rev-parse", "HEAD"], env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn try_git_version_is_right() {
+	const VERSION: Option<&str> = try_git_version!();
+	assert!(VERSION == Some(git_version!()));
+	assert!(try_git_version!(prefix = "git:") == Some(git_version!(prefix = "git:")));
+	assert!(try_git_version!(env = { GIT_DIR = "/nonexistent" }) == None);
+}
+
+git_version::assert_tag_matches_cargo!();
+
+#[test]
+fn assert_tag_matches_cargo_ignores_other_tags() {
+	git_version::assert_tag_matches_cargo!(prefix = "no-such-prefix-v");
+	git_version::assert_tag_matches_cargo!(env = { GIT_DIR = "/nonexistent" });
+	git_version::assert_tag_matches_cargo!(env = { PATH = "" }, ignore_git_errors = true);
+}
+
+#[test]
+fn git_ahead_behind_falls_back() {
+	const RELEASE: (&str, usize, usize) = git_ahead_behind!(pattern = "no-such-branch/*", fallback = ("", 0, 0));
+	assert!(RELEASE == ("", 0, 0));
+}
+
+#[test]
+fn git_ahead_behind_upstream_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git")
+		.args(["rev-list", "--left-right", "--count", "HEAD...@{upstream}"])
+		.output());
+	let counts = String::from_utf8_lossy(&output.stdout);
+	let counts: Vec<usize> = counts.split_whitespace().filter_map(|x| x.parse().ok()).collect();
+	let (_, ahead, behind) = git_ahead_behind!(fallback = ("", usize::MAX, usize::MAX));
+	if output.status.success() {
+		assert!([ahead, behind] == counts[..]);
+	} else {
+		assert!((ahead, behind) == (usize::MAX, usize::MAX));
+	}
+}
+
+#[test]
+fn git_trailer_falls_back() {
+	assert!(git_trailer!(key = "No-Such-Trailer", fallback = "none") == "none");
+}
+
+#[test]
+fn git_release_channel_falls_back() {
+	assert!(git_release_channel!(rules = [("tag:no-such-tag-*", "stable")], fallback = "nightly") == "nightly");
+}
+
+#[test]
+fn git_commit_date_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["log", "-1", "--format=%ct"]).output());
+	let_assert!(Ok(timestamp) = String::from_utf8_lossy(&output.stdout).trim().parse::<i64>());
+	assert!(git_commit_date!(format = "%s") == timestamp.to_string());
+	assert!(git_commit_date!(format = "%s", tz = "local") == timestamp.to_string());
+	assert!(git_commit_date!().len() == "2000-01-01".len());
+	assert!(git_version!(combine_format = "{git} ({date})").ends_with(&format!("({})", git_commit_date!())));
+	assert!(git_commit_date!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_workspace_versions_is_right() {
+	const VERSIONS: &[(&str, &str)] = git_workspace_versions!(as_slice = true);
+	let names: Vec<_> = VERSIONS.iter().map(|(name, _)| *name).collect();
+	assert!(names == ["git-version", "git-version-core", "git-version-macro", "git-version-testkit"]);
+	for (name, commit) in VERSIONS {
+		let dir = Path::new("..").join(name);
+		let_assert!(Ok(output) = std::process::Command::new("git").args(["log", "-1", "--format=%H", "--"]).arg(dir).output());
+		assert!(*commit == String::from_utf8_lossy(&output.stdout).trim());
+	}
+
+	let short = git_workspace_versions!(short = 10, env = { GIT_DIR = "/nonexistent" }, fallback = "unknown");
+	assert!(short.iter().all(|(_, commit)| *commit == "unknown"));
+}
+
+#[test]
+fn git_path_version_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git")
+		.args(["log", "-1", "--date=format-local:%Y-%m-%d", "--format=%H %cd", "--", "src"])
+		.env("TZ", "UTC")
+		.output());
+	let expected = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+	let_assert!(Some((hash, date)) = expected.split_once(' '));
+	assert!(git_path_version!(path = "src") == hash);
+	assert!(git_path_version!(path = "src", format = "{hash} {date}") == expected);
+	assert!(git_path_version!(path = "src", short = 7).len() >= 7);
+	assert!(hash.starts_with(git_path_version!(path = "src", short = 7)));
+	assert!(git_path_version!(path = "src", format = "{{{date}}}") == format!("{{{}}}", date));
+	assert!(git_path_version!(path = "no-such-file", fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_first_parent_count_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["rev-list", "--first-parent", "--count", "HEAD"]).output());
+	let_assert!(Ok(total) = String::from_utf8_lossy(&output.stdout).trim().parse::<usize>());
+	assert!(git_first_parent_count!(pattern = "no-such-tag-*") == total);
+	assert!(git_first_parent_count!(env = { GIT_DIR = "/nonexistent" }, fallback = 7) == 7);
+}
+
+#[test]
+fn git_commit_count_is_right() {
+	let count = |args: &[&str]| {
+		let_assert!(Ok(output) = std::process::Command::new("git").args(["rev-list", "--count", "HEAD"]).args(args).output());
+		let_assert!(Ok(count) = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>());
+		count
+	};
+	const TOTAL: u64 = git_commit_count!();
+	assert!(TOTAL == count(&[]));
+	assert!(git_commit_count!(path = "src") == count(&["--", "src"]));
+	assert!(git_commit_count!(path = "src") <= TOTAL);
+	assert!(git_commit_count!(since = "no-such-tag", fallback = 7) == 7);
+	assert!(git_commit_count!(env = { GIT_DIR = "/nonexistent" }, fallback = 7) == 7);
+}
+
+#[test]
+fn git_commits_since_tag_is_right() {
+	let parsed = ParsedVersion::parse(git_version!(args = ["--always", "--long"]));
+	const COMMITS: usize = git_commits_since_tag!(fallback = usize::MAX);
+	match parsed.tag {
+		Some(_) => assert!(COMMITS == parsed.distance),
+		None => assert!(COMMITS == usize::MAX),
+	}
+	assert!(git_commits_since_tag!(env = { GIT_DIR = "/nonexistent" }, fallback = 7) == 7);
+}
+
+#[test]
+fn git_head_tags_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["tag", "--list", "--points-at", "HEAD"]).output());
+	let mut expected: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(String::from).collect();
+	expected.sort();
+	const TAGS: &[&str] = git_head_tags!(as_slice = true);
+	assert!(TAGS == expected);
+	assert!(git_head_tags!() == TAGS);
+	static STATIC_TAGS: &[&str] = git_head_tags!(as_slice = true, static_ref = true);
+	assert!(STATIC_TAGS == TAGS);
+	assert!(git_head_tags!(env = { GIT_DIR = "/nonexistent" }, fallback = ["unknown"]) == ["unknown"]);
+}
+
+#[test]
+fn git_remote_url_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["remote", "get-url", "origin"]).output());
+	const URL: &str = git_remote_url!(fallback = "unknown");
+	if output.status.success() {
+		let url = String::from_utf8_lossy(&output.stdout);
+		assert!(url.trim().ends_with(URL.rsplit('@').next().unwrap()));
+	} else {
+		assert!(URL == "unknown");
+	}
+	assert!(git_remote_url!(remote = "origin", fallback = "unknown") == URL);
+	assert!(git_remote_url!(remote = "no-such-remote", fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_commit_author_and_committer_are_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["log", "-1", "--format=%an%n%ae%n%cn%n%ce"]).output());
+	let output = String::from_utf8_lossy(&output.stdout);
+	let lines: Vec<&str> = output.lines().collect();
+	assert!(git_commit_author!() == lines[0]);
+	assert!(git_commit_author!(format = "{email}") == lines[1]);
+	assert!(git_commit_committer!() == lines[2]);
+	assert!(git_commit_committer!(format = "{name} <{email}>") == format!("{} <{}>", lines[2], lines[3]));
+	assert!(git_commit_author!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_commit_message_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["log", "-1", "--format=%s"]).output());
+	let subject = String::from_utf8_lossy(&output.stdout).trim_end().to_owned();
+	assert!(git_commit_message!() == subject);
+	const SHORT: &str = git_commit_message!(max_len = 10);
+	assert!(SHORT.len() <= 10);
+	assert!(subject.starts_with(SHORT));
+	assert!(git_commit_message!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_tree_hash_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["rev-parse", "HEAD^{tree}"]).output());
+	let tree = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+	assert!(git_tree_hash!() == tree);
+	assert!(tree.starts_with(git_tree_hash!(short = 7)));
+	assert!(git_tree_hash!() != git_commit_hash!());
+	assert!(git_tree_hash!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_version_of_ref() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["describe", "--always", "HEAD~1"]).output());
+	let expected = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+	assert!(git_version!(ref = "HEAD~1") == expected);
+	assert!(git_version!(ref = "HEAD~1", args = ["--always", "--dirty=-changed", "--broken"]) == expected);
+	assert!(git_version!(ref = "HEAD", format = "{sha}") == git_commit_hash!(short = 7));
+	assert!(git_version!(ref = "no-such-ref", fallback = "unknown") == "unknown");
+	assert!(git_version_command!(ref = "HEAD~1").contains(" describe --always --end-of-options "));
+}
+
+#[test]
+fn git_head_sha_is_right() {
+	let hash = git_commit_hash!();
+	assert!(git_head_sha!() == hash);
+	assert!(git_head_sha!(short = 8) == &hash[..8]);
+	assert!(git_head_sha!(short = 12, uppercase = true) == hash[..12].to_ascii_uppercase());
+	assert!(git_head_sha!(short = 100).len() == hash.len());
+	assert!(git_head_sha!(env = { GIT_DIR = "/nonexistent" }, fallback = "00000000") == "00000000");
+}
+
+#[test]
+fn git_sha_ints_are_right() {
+	const SHA_U64: u64 = git_sha_u64!();
+	const SHA_U128: u128 = git_sha_u128!();
+	let hash = git_commit_hash!();
+	assert!(format!("{:016x}", SHA_U64) == hash[..16]);
+	assert!(format!("{:032x}", SHA_U128) == hash[..32]);
+	assert!(git_sha_u64!(env = { GIT_DIR = "/nonexistent" }, fallback = 0) == 0);
+}
+
+#[test]
+fn git_sha_bytes_is_right() {
+	const SHA: [u8; 20] = git_sha_bytes!();
+	const SHA_SLICE: &[u8] = git_sha_bytes!(as_slice = true);
+	let hex: String = SHA.iter().map(|x| format!("{:02x}", x)).collect();
+	assert!(hex == git_commit_hash!());
+	assert!(SHA_SLICE == SHA);
+	assert!(git_sha_bytes!(static_ref = true) == &SHA);
+	assert!(git_sha_bytes!(env = { GIT_DIR = "/nonexistent" }, fallback = [0; 20]) == [0; 20]);
+}
+
+#[test]
+fn git_is_at_tag_is_right() {
+	const AT_TAG: bool = git_is_at_tag!("no-such-tag");
+	assert!(!AT_TAG);
+	assert!(!git_is_at_tag!("no-such-tag", descendant = true));
+	assert!(git_is_at_tag!("no-such-tag", descendant = true, env = { GIT_DIR = "/nonexistent" }, fallback = true));
+}
+
+#[test]
+fn git_signature_status_is_right() {
+	let_assert!(Ok(output) = std::process::Command::new("git").args(["log", "-1", "--format=%G?"]).output());
+	if String::from_utf8_lossy(&output.stdout).trim() == "N" {
+		assert!(git_signature_status!() == "unsigned");
+		assert!(git_signature_status!(format = "{signer}") == "unsigned");
+	}
+	assert!(git_signature_status!(env = { GIT_DIR = "/nonexistent" }, fallback = "unknown") == "unknown");
+}
+
+#[test]
+fn git_dirty_files_is_right() {
+	const FILES: &[&str] = git_dirty_files!(as_slice = true);
+	const TRACKED: &[&str] = git_dirty_files!(untracked = "no", as_slice = true);
+	assert!(FILES.is_empty() == !git_dirty!(untracked = "normal"));
+	assert!(TRACKED.is_empty() == !git_dirty!());
+	assert!(TRACKED.iter().all(|x| FILES.contains(x)));
+	assert!(FILES.windows(2).all(|x| x[0] < x[1]));
+	assert!(git_dirty_files!(max_count = 1, as_slice = true).len() == FILES.len().min(1));
+	assert!(git_dirty_files!(dirty_exclude = ["*"], as_slice = true).is_empty());
+	assert!(git_dirty_files!(env = { GIT_DIR = "/nonexistent" }, fallback = ["unknown"]) == ["unknown"]);
+}
+
+#[test]
+fn parsed_version_splits_describe_output() {
+	let parse = ParsedVersion::parse;
+	assert!(parse("v1.2") == ParsedVersion { tag: Some("v1.2"), distance: 0, hash: None, dirty: false });
+	assert!(parse("v1.2-3-gabcdef1") == ParsedVersion { tag: Some("v1.2"), distance: 3, hash: Some("abcdef1"), dirty: false });
+	assert!(parse("v1-rc-12-g0123abc-modified") == ParsedVersion { tag: Some("v1-rc"), distance: 12, hash: Some("0123abc"), dirty: true });
+	assert!(parse("0123abc-dirty") == ParsedVersion { tag: None, distance: 0, hash: Some("0123abc"), dirty: true });
+	assert!(parse("release-2024") == ParsedVersion { tag: Some("release-2024"), distance: 0, hash: None, dirty: false });
+	assert!(parse("v1.0-0-gabc").distance == 0);
+
+	let custom = ParsedVersion::parse_with_dirty_marker("v1.2-3-gabcdef1+wip", "+wip");
+	assert!(custom == ParsedVersion { tag: Some("v1.2"), distance: 3, hash: Some("abcdef1"), dirty: true });
+
+	const VERSION: ParsedVersion = ParsedVersion::parse(git_version!(args = ["--long", "--always", "--dirty=-modified"]));
+	assert!(VERSION.hash.is_some());
+}
+
+#[test]
+fn submodule_versions_map_looks_up_paths() {
+	const SUBMODULES: SubmoduleVersions = git_submodule_versions_map!(fallback = "unknown");
+	assert!(SUBMODULES.is_empty());
+	assert!(SUBMODULES.get("vendor/foo") == None);
+
+	const MAP: SubmoduleVersions = SubmoduleVersions::new(&[("a", "v1"), ("a/b", "v2"), ("b", "v3"), ("vendor/foo", "v4")]);
+	const FOO: Option<&str> = MAP.get("vendor/foo");
+	assert!(FOO == Some("v4"));
+	assert!(MAP.get("a") == Some("v1"));
+	assert!(MAP.get("a/b") == Some("v2"));
+	assert!(MAP.get("b") == Some("v3"));
+	assert!(MAP.get("a/") == None);
+	assert!(MAP.get("") == None);
+	assert!(MAP.len() == 4);
+	assert!(MAP.entries()[2] == ("b", "v3"));
+
+	let unsorted = std::panic::catch_unwind(|| SubmoduleVersions::new(&[("b", "v1"), ("a", "v2")]));
+	assert!(let Err(_) = unsorted);
+	let duplicate = std::panic::catch_unwind(|| SubmoduleVersions::new(&[("a", "v1"), ("a", "v2")]));
+	assert!(let Err(_) = duplicate);
+}
+
+#[test]
+fn git_version_uses_env() {
+	assert!(git_version!(env = { GIT_DIR = "/nonexistent" }, fallback = "none") == "none");
+	assert!(git_version!(inherit_git_env = true) == git_version!());
+	assert!(git_version!(budget_ms = 60000) == git_version!());
+	assert!(git_version!(budget_ms = 0, fallback = "late") == "late");
+	let_assert!(Ok(head) = std::process::Command::new("git").args(["rev-parse", "HEAD"]).output());
+	let head = String::from_utf8_lossy(&head.stdout);
+	let version = git_version!(
+		args = ["--always", "--exclude=*"],
+		env = { GIT_CONFIG_COUNT = "1", GIT_CONFIG_KEY_0 = "core.abbrev", GIT_CONFIG_VALUE_0 = "16" },
+	);
+	assert!(version == &head[..16]);
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+	git_version::wasm_git_version!(prefix = "git:");
+
+	#[test]
+	fn wasm_git_version_is_right() {
+		use assert2::assert;
+		assert!(git_version() == git_version::git_version!(prefix = "git:"));
+	}
 }
 
 #[test]